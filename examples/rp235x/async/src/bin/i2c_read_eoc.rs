@@ -1,5 +1,5 @@
-//! This example shows how to read sensor data using Embassy. Instead of waiting, it uses the EOC
-//! pin to check for a pulse to know when data is ready.
+//! This example shows how to read sensor data using Embassy. Instead of waiting a fixed delay, the
+//! driver waits for the EOC pin to go high to know when data is ready.
 
 #![no_std]
 #![no_main]
@@ -7,8 +7,11 @@
 use defmt::*;
 use embassy_rp::gpio::{Input, Pull};
 use embassy_rp::i2c::InterruptHandler;
+use embassy_time::Delay;
 use {defmt_rtt as _, panic_probe as _};
-use honeywell_mpr::{Mpr, MprConfig, TransferFunction};
+use honeywell_mpr::{Mpr, MprConfig, PressureUnit, TransferFunction};
+
+const EOC_TIMEOUT_MS: u32 = 50;
 
 embassy_rp::bind_interrupts!(struct Irqs {
     I2C1_IRQ => InterruptHandler<embassy_rp::peripherals::I2C1>;
@@ -24,13 +27,11 @@ async fn main(_task_spawner: embassy_executor::Spawner) {
     config.frequency = 400_000;
     let bus = embassy_rp::i2c::I2c::new_async(p.I2C1, scl, sda, Irqs, config);
 
-    let config = MprConfig::new(0, 25, TransferFunction::C);
+    let config = MprConfig::new(0.0, 25.0, PressureUnit::Psi, TransferFunction::C);
     let mut sensor = Mpr::new_i2c(bus, 0x18, config).unwrap();
 
     loop {
-        sensor.exit_standby().await.unwrap();
-        eoc.wait_for_high().await;
-        match sensor.read().await {
+        match sensor.read_with_eoc(&mut eoc, Delay, Some(EOC_TIMEOUT_MS)).await {
             Ok(reading) => {
                 info!(
                     "bar: {}, inHg: {}, mmHg: {}, kPa: {}, psi: {}",
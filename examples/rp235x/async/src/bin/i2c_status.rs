@@ -6,7 +6,7 @@
 use defmt::*;
 use embassy_rp::i2c::InterruptHandler;
 use {defmt_rtt as _, panic_probe as _};
-use honeywell_mpr::{Mpr, MprConfig, TransferFunction};
+use honeywell_mpr::{Mpr, MprConfig, PressureUnit, TransferFunction};
 
 embassy_rp::bind_interrupts!(struct Irqs {
     I2C1_IRQ => InterruptHandler<embassy_rp::peripherals::I2C1>;
@@ -21,7 +21,7 @@ async fn main(_task_spawner: embassy_executor::Spawner) {
     config.frequency = 400_000;
     let bus = embassy_rp::i2c::I2c::new_async(p.I2C1, scl, sda, Irqs, config);
 
-    let config = MprConfig::new(0, 25, TransferFunction::C);
+    let config = MprConfig::new(0.0, 25.0, PressureUnit::Psi, TransferFunction::C);
     let mut sensor = Mpr::new_i2c(bus, 0x18, config).unwrap();
 
     loop {
@@ -0,0 +1,46 @@
+//! This example shows how to continuously sample sensor data using Embassy. The driver starts the
+//! next conversion right after each read and paces readings to a fixed period.
+
+#![no_std]
+#![no_main]
+
+use defmt::*;
+use embassy_rp::i2c::InterruptHandler;
+use embassy_time::{Delay, Instant};
+use {defmt_rtt as _, panic_probe as _};
+use honeywell_mpr::{Clock, Mpr, MprConfig, MprI2cError, PressureUnit, TransferFunction};
+
+const PERIOD_US: u32 = 20_000;
+
+embassy_rp::bind_interrupts!(struct Irqs {
+    I2C1_IRQ => InterruptHandler<embassy_rp::peripherals::I2C1>;
+});
+
+struct EmbassyClock;
+impl Clock for EmbassyClock {
+    fn now_us(&mut self) -> u64 {
+        Instant::now().as_micros()
+    }
+}
+
+#[embassy_executor::main]
+async fn main(_task_spawner: embassy_executor::Spawner) {
+    let p = embassy_rp::init(Default::default());
+    let sda = p.PIN_14;
+    let scl = p.PIN_15;
+    let mut config = embassy_rp::i2c::Config::default();
+    config.frequency = 400_000;
+    let bus = embassy_rp::i2c::I2c::new_async(p.I2C1, scl, sda, Irqs, config);
+
+    let config = MprConfig::new(0.0, 25.0, PressureUnit::Psi, TransferFunction::C);
+    let mut sensor = Mpr::new_i2c(bus, 0x18, config).unwrap();
+    let mut stream = sensor.stream(PERIOD_US, EmbassyClock, Delay);
+
+    loop {
+        match stream.next_reading().await {
+            Ok(reading) => info!("psi: {}", reading.psi()),
+            Err(MprI2cError::Overrun) => warn!("overrun :("),
+            Err(_) => error!("read failed :(")
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! This example shows how to read the sensor from a main loop that must never block: the
+//! conversion is triggered, then polled on each pass while other work continues.
+
+#![no_std]
+#![no_main]
+
+use defmt::{error, info};
+use defmt_rtt as _;
+use embedded_hal::delay::DelayNs;
+// Ensure we halt the program on panic (if we don't mention this crate it won't
+// be linked)
+use panic_halt as _;
+
+// Alias for our HAL crate
+use rp235x_hal as hal;
+
+// Some things we need
+use hal::fugit::RateExtU32;
+use hal::gpio::{FunctionI2C, Pin};
+use honeywell_mpr::blocking::Mpr;
+use honeywell_mpr::{MprConfig, MprI2cError, PressureUnit, TransferFunction};
+
+/// Tell the Boot ROM about our application
+#[unsafe(link_section = ".start_block")]
+#[used]
+pub static IMAGE_DEF: hal::block::ImageDef = hal::block::ImageDef::secure_exe();
+
+/// External high-speed crystal on the Raspberry Pi Pico 2 board is 12 MHz.
+/// Adjust if your board has a different frequency
+const XTAL_FREQ_HZ: u32 = 12_000_000u32;
+
+/// Entry point to our bare-metal application.
+///
+/// The `#[hal::entry]` macro ensures the Cortex-M start-up code calls this function
+/// as soon as all global variables and the spinlock are initialised.
+///
+/// The function configures the rp235x peripherals, then performs a single I²C
+/// write to a fixed address.
+#[hal::entry]
+fn main() -> ! {
+    let mut pac = hal::pac::Peripherals::take().unwrap();
+
+    // Set up the watchdog driver - needed by the clock setup code
+    let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
+
+    // Configure the clocks
+    let clocks = hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+        .unwrap();
+
+    let mut timer = hal::Timer::new_timer0(pac.TIMER0, &mut pac.RESETS, &clocks);
+
+    // The single-cycle I/O block controls our GPIO pins
+    let sio = hal::Sio::new(pac.SIO);
+
+    // Set the pins to their default state
+    let pins = hal::gpio::Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+
+    // Configure two pins as being I²C, not GPIO
+    let sda_pin: Pin<_, FunctionI2C, _> = pins.gpio14.reconfigure();
+    let scl_pin: Pin<_, FunctionI2C, _> = pins.gpio15.reconfigure();
+    // let not_an_scl_pin: Pin<_, FunctionI2C, PullUp> = pins.gpio20.reconfigure();
+
+    // Create the I²C drive, using the two pre-configured pins. This will fail
+    // at compile time if the pins are in the wrong mode, or if this I²C
+    // peripheral isn't available on these pins!
+    let bus = hal::I2C::i2c1(
+        pac.I2C1,
+        sda_pin,
+        scl_pin, // Try `not_an_scl_pin` here
+        400.kHz(),
+        &mut pac.RESETS,
+        &clocks.system_clock,
+    );
+
+    let config = MprConfig::new(0.0, 25.0, PressureUnit::Psi, TransferFunction::C);
+    let mut sensor = Mpr::new_i2c(bus, 0x18, config).unwrap();
+
+    let mut next_trigger_us = 0;
+    loop {
+        let now_us = timer.get_counter().ticks();
+        if now_us >= next_trigger_us {
+            if sensor.trigger().is_err() {
+                error!("trigger failed :(");
+            }
+            next_trigger_us = now_us + 3_000_000;
+        }
+        match sensor.poll() {
+            Ok(reading) => info!("psi: {}", reading.psi()),
+            Err(nb::Error::WouldBlock) | Err(nb::Error::Other(MprI2cError::NotTriggered)) => {},
+            Err(nb::Error::Other(_)) => error!("read failed :(")
+        }
+        // other time-critical work goes here
+        timer.delay_us(100);
+    }
+}
+
+/// Program metadata for `picotool info`
+#[unsafe(link_section = ".bi_entries")]
+#[used]
+pub static PICOTOOL_ENTRIES: [hal::binary_info::EntryAddr; 5] = [
+    hal::binary_info::rp_cargo_bin_name!(),
+    hal::binary_info::rp_cargo_version!(),
+    hal::binary_info::rp_program_description!(c"I²C Non-blocking Example"),
+    hal::binary_info::rp_cargo_homepage_url!(),
+    hal::binary_info::rp_program_build_attribute!(),
+];
\ No newline at end of file
@@ -14,7 +14,8 @@ use rp235x_hal as hal;
 // Some things we need
 use hal::fugit::RateExtU32;
 use hal::gpio::{FunctionI2C, Pin};
-use honeywell_mpr::{Mpr, MprConfig, TransferFunction};
+use honeywell_mpr::blocking::Mpr;
+use honeywell_mpr::{MprConfig, PressureUnit, TransferFunction};
 
 /// Tell the Boot ROM about our application
 #[unsafe(link_section = ".start_block")]
@@ -81,7 +82,7 @@ fn main() -> ! {
         &clocks.system_clock,
     );
 
-    let config = MprConfig::new(0, 25, TransferFunction::C);
+    let config = MprConfig::new(0.0, 25.0, PressureUnit::Psi, TransferFunction::C);
     let mut sensor = Mpr::new_i2c(bus, 0x18, config).unwrap();
 
     loop {
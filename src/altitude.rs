@@ -0,0 +1,169 @@
+//! Barometric altitude from absolute-reference parts.
+//!
+//! Uses the international barometric formula for the troposphere (ISA, valid to ~11km):
+//! `h = 44330.8 * (1 - (p / qnh) ^ 0.190263)`.
+
+use crate::Reading;
+
+/// ISA mean sea level pressure.
+pub const STANDARD_QNH_PA: f32 = 101_325.0;
+
+const ALTITUDE_SCALE_M: f32 = 44_330.8;
+const EXPONENT: f32 = 0.190_263;
+
+/// Converts absolute pressure to altitude above the sea-level reference pressure (QNH).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Altimeter {
+    qnh_pa: f32
+}
+impl Default for Altimeter {
+    fn default() -> Self {
+        Self::new(STANDARD_QNH_PA)
+    }
+}
+impl Altimeter {
+    pub fn new(qnh_pa: f32) -> Self {
+        Self { qnh_pa }
+    }
+
+    pub fn qnh_pa(&self) -> f32 {
+        self.qnh_pa
+    }
+
+    /// Sets the sea-level reference, e.g. from a METAR (hPa * 100).
+    pub fn set_qnh_pa(&mut self, qnh_pa: f32) {
+        self.qnh_pa = qnh_pa;
+    }
+
+    /// Derives the sea-level reference from a reading taken at a known altitude.
+    pub fn set_qnh_from_altitude(&mut self, reading: &Reading, altitude_m: f32) {
+        let ratio = 1.0 - altitude_m / ALTITUDE_SCALE_M;
+        self.qnh_pa = pascals(reading) / libm::powf(ratio, 1.0 / EXPONENT);
+    }
+
+    /// Altitude in meters of an absolute reading.
+    pub fn altitude_m(&self, reading: &Reading) -> f32 {
+        self.altitude_m_from_pa(pascals(reading))
+    }
+
+    /// Altitude in meters of an absolute pressure in pascals.
+    pub fn altitude_m_from_pa(&self, pressure_pa: f32) -> f32 {
+        ALTITUDE_SCALE_M * (1.0 - libm::powf(pressure_pa / self.qnh_pa, EXPONENT))
+    }
+}
+
+fn pascals(reading: &Reading) -> f32 {
+    reading.kpa() * 1_000.0
+}
+
+/// Estimates vertical speed from consecutive altitudes, smoothed by a first-order low-pass
+/// filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerticalSpeed {
+    time_constant_s: f32,
+    last: Option<(u64, f32)>,
+    speed_m_s: Option<f32>
+}
+impl VerticalSpeed {
+    /// `time_constant_s` sets the smoothing; 0 returns the raw difference of the last two
+    /// altitudes.
+    pub fn new(time_constant_s: f32) -> Self {
+        Self { time_constant_s, last: None, speed_m_s: None }
+    }
+
+    /// Adds an altitude taken at `now_us` (e.g. from a `Clock`) and returns the vertical speed in
+    /// m/s, positive when climbing. Returns `None` until two altitudes have been added; altitudes
+    /// that are not later than the previous one are ignored.
+    pub fn update(&mut self, now_us: u64, altitude_m: f32) -> Option<f32> {
+        let Some((last_us, last_altitude_m)) = self.last else {
+            self.last = Some((now_us, altitude_m));
+            return None
+        };
+        if now_us <= last_us {
+            return self.speed_m_s
+        }
+        self.last = Some((now_us, altitude_m));
+        let dt_s = (now_us - last_us) as f32 / 1_000_000.0;
+        let speed_m_s = (altitude_m - last_altitude_m) / dt_s;
+        let smoothed = match self.speed_m_s {
+            Some(previous) => previous + dt_s / (self.time_constant_s + dt_s) * (speed_m_s - previous),
+            None => speed_m_s
+        };
+        self.speed_m_s = Some(smoothed);
+        self.speed_m_s
+    }
+
+    /// Last estimate in m/s, if any.
+    pub fn speed_m_s(&self) -> Option<f32> {
+        self.speed_m_s
+    }
+
+    /// Forgets previous altitudes, e.g. after a gap in readings.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.speed_m_s = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use crate::{PressureUnit, TransferFunction, ZeroOffset};
+    use crate::sim::test_config;
+    use super::*;
+
+    // 0 to 25 psi absolute, one count is ~0.01 Pa
+    fn reading(pressure_pa: f32) -> Reading {
+        let config = test_config();
+        let span = TransferFunction::A.max_counts() - TransferFunction::A.min_counts();
+        let psi = PressureUnit::Kpa.to_psi(pressure_pa / 1_000.0);
+        let raw_data = (psi / 25.0 * span + TransferFunction::A.min_counts() + 0.5) as u32;
+        config.reading(raw_data, ZeroOffset::default())
+    }
+
+    #[test]
+    fn altitude_standard_atmosphere_ok() {
+        let altimeter = Altimeter::default();
+        assert!(relative_eq!(altimeter.altitude_m_from_pa(101_325.0), 0.0, epsilon = 1e-2));
+        assert!(relative_eq!(altimeter.altitude_m_from_pa(89_874.6), 1_000.0, epsilon = 0.5));
+        assert!(relative_eq!(altimeter.altitude_m_from_pa(54_019.9), 5_000.0, epsilon = 2.0));
+        assert!(relative_eq!(altimeter.altitude_m(&reading(89_874.6)), 1_000.0, epsilon = 0.5));
+    }
+
+    #[test]
+    fn altitude_qnh_ok() {
+        let mut altimeter = Altimeter::default();
+        altimeter.set_qnh_pa(102_000.0);
+        assert!(relative_eq!(altimeter.altitude_m_from_pa(102_000.0), 0.0, epsilon = 1e-2));
+        assert!(altimeter.altitude_m_from_pa(101_325.0) > 50.0);
+    }
+
+    #[test]
+    fn set_qnh_from_altitude_ok() {
+        let mut altimeter = Altimeter::default();
+        let reading = reading(95_000.0);
+        altimeter.set_qnh_from_altitude(&reading, 250.0);
+        assert!(relative_eq!(altimeter.altitude_m(&reading), 250.0, epsilon = 0.1));
+        assert!(altimeter.qnh_pa() > 95_000.0);
+    }
+
+    #[test]
+    fn vertical_speed_raw_ok() {
+        let mut vertical_speed = VerticalSpeed::new(0.0);
+        assert_eq!(vertical_speed.update(0, 100.0), None);
+        assert_eq!(vertical_speed.update(500_000, 101.0), Some(2.0));
+        assert_eq!(vertical_speed.update(500_000, 150.0), Some(2.0));
+        assert_eq!(vertical_speed.update(1_500_000, 100.0), Some(-1.0));
+        vertical_speed.reset();
+        assert_eq!(vertical_speed.speed_m_s(), None);
+    }
+
+    #[test]
+    fn vertical_speed_smoothed_ok() {
+        let mut vertical_speed = VerticalSpeed::new(1.0);
+        vertical_speed.update(0, 0.0);
+        assert_eq!(vertical_speed.update(1_000_000, 1.0), Some(1.0));
+        // a step to 3 m/s moves halfway with a 1s time constant and 1s interval
+        assert_eq!(vertical_speed.update(2_000_000, 4.0), Some(2.0));
+    }
+}
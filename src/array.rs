@@ -0,0 +1,129 @@
+use core::array;
+use core::mem;
+use crate::{MprArrayError, Reading};
+use super::{bisync, DelayNs};
+use super::driver::{Mpr, EXIT_STANDBY_DELAY_MS};
+use super::interface::Interface;
+
+/// Several sensors, e.g. up to eight on one I2C bus shared with `embedded-hal-bus`, measured in
+/// parallel.
+///
+/// All conversions are started back-to-back and run concurrently, so a sweep takes one
+/// conversion time instead of one per sensor.
+pub struct MprArray<I, const N: usize> {
+    sensors: [Mpr<I>; N]
+}
+impl<I: Interface, const N: usize> MprArray<I, N> {
+    /// Fails if two I2C sensors share an address, as both would answer every transaction.
+    pub fn new(sensors: [Mpr<I>; N]) -> Result<Self, MprArrayError> {
+        for (i, sensor) in sensors.iter().enumerate() {
+            if let Some(address) = sensor.address()
+                && sensors[..i].iter().any(|other| other.address() == Some(address)) {
+                return Err(MprArrayError::DuplicateAddress(address))
+            }
+        }
+        Ok(Self { sensors })
+    }
+
+    /// Returns the sensors.
+    pub fn release(self) -> [Mpr<I>; N] {
+        self.sensors
+    }
+
+    pub fn sensors_mut(&mut self) -> &mut [Mpr<I>; N] {
+        &mut self.sensors
+    }
+
+    /// Starts a conversion on every sensor, waits once and reads them all.
+    ///
+    /// Results are in sensor order; a sensor that fails to start its conversion reports that
+    /// error and is not read.
+    #[bisync]
+    pub async fn read_with_delay<D: DelayNs>(&mut self, mut delay: D) -> [Result<Reading, I::Error>; N] {
+        let mut raw_data: [Result<u32, I::Error>; N] = array::from_fn(|_| Ok(0));
+        for (sensor, raw_data) in self.sensors.iter_mut().zip(raw_data.iter_mut()) {
+            if let Err(e) = sensor.exit_standby().await {
+                *raw_data = Err(e);
+            }
+        }
+        delay.delay_ms(EXIT_STANDBY_DELAY_MS).await;
+        for (sensor, raw_data) in self.sensors.iter_mut().zip(raw_data.iter_mut()) {
+            if raw_data.is_ok() {
+                *raw_data = sensor.read_raw().await;
+            }
+        }
+        array::from_fn(|i| mem::replace(&mut raw_data[i], Ok(0)).and_then(|raw_data| self.sensors[i].reading(raw_data)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use crate::MprI2cError;
+    use crate::sim::{test_config, SimClock, SimFaults, SimMpr};
+    use super::super::block_on;
+    use super::*;
+
+    #[test]
+    fn read_with_delay_ok() {
+        let clock = SimClock::new();
+        let sims = [0, 1, 2].map(|_| SimMpr::with_clock(test_config(), &clock));
+        for (i, sim) in sims.iter().enumerate() {
+            sim.set_address(0x18 + 0x10 * i as u8);
+            sim.set_pressure(5.0 * (i + 1) as f32);
+            sim.set_conversion_time_us(5_000 + 2_000 * i as u32);
+        }
+        let mut array = MprArray::new([
+            Mpr::sim_i2c(&sims[0]),
+            Mpr::new_i2c(sims[1].i2c(), 0x28, test_config()).unwrap(),
+            Mpr::new_i2c(sims[2].i2c(), 0x38, test_config()).unwrap(),
+        ]).unwrap();
+        let results = block_on(array.read_with_delay(sims[0].delay()));
+        for (i, result) in results.iter().enumerate() {
+            assert!(relative_eq!(result.as_ref().unwrap().psi(), 5.0 * (i + 1) as f32, epsilon = 1e-4));
+        }
+        assert_eq!(clock.now_us(), EXIT_STANDBY_DELAY_MS as u64 * 1_000);
+    }
+
+    #[test]
+    fn read_with_delay_conversion_longer_than_delay() {
+        let clock = SimClock::new();
+        let sims = [SimMpr::with_clock(test_config(), &clock), SimMpr::with_clock(test_config(), &clock)];
+        sims[1].set_address(0x28);
+        sims[1].set_conversion_time_us(EXIT_STANDBY_DELAY_MS * 1_000 + 1);
+        let mut array = MprArray::new([
+            Mpr::sim_i2c(&sims[0]),
+            Mpr::new_i2c(sims[1].i2c(), 0x28, test_config()).unwrap(),
+        ]).unwrap();
+        let [first, second] = block_on(array.read_with_delay(sims[1].delay()));
+        assert!(first.is_ok());
+        assert!(matches!(second, Err(MprI2cError::Busy)));
+    }
+
+    #[test]
+    fn new_duplicate_address() {
+        let sims = [SimMpr::new(test_config()), SimMpr::new(test_config())];
+        let result = MprArray::new([
+            Mpr::sim_i2c(&sims[0]),
+            Mpr::sim_i2c(&sims[1]),
+        ]);
+        assert!(matches!(result, Err(MprArrayError::DuplicateAddress(0x18))));
+    }
+
+    #[test]
+    fn read_with_delay_partial_failure() {
+        let clock = SimClock::new();
+        let sims = [SimMpr::with_clock(test_config(), &clock), SimMpr::with_clock(test_config(), &clock)];
+        sims[0].set_pressure(5.0);
+        sims[1].set_address(0x28);
+        sims[1].set_faults(SimFaults { nak: true, ..Default::default() });
+        let mut array = MprArray::new([
+            Mpr::sim_i2c(&sims[0]),
+            Mpr::new_i2c(sims[1].i2c(), 0x28, test_config()).unwrap(),
+        ]).unwrap();
+        let [first, second] = block_on(array.read_with_delay(sims[0].delay()));
+        assert!(relative_eq!(first.unwrap().psi(), 5.0, epsilon = 1e-4));
+        assert!(matches!(second, Err(MprI2cError::I2c(_))));
+        assert_eq!(array.release().len(), 2);
+    }
+}
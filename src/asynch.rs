@@ -0,0 +1,61 @@
+//! Async driver built on `embedded_hal_async`.
+//!
+//! Shares its sources with `blocking`; only the HAL traits, the EOC wait and the `trigger` state
+//! differ.
+
+use core::marker::PhantomData;
+use embassy_futures::select::{select, Either};
+pub(crate) use embedded_hal_async::delay::DelayNs;
+pub(crate) use embedded_hal_async::digital::Wait as Eoc;
+pub(crate) use embedded_hal_async::i2c::I2c;
+pub(crate) use embedded_hal_async::spi::SpiDevice;
+pub(crate) use maybe_async::must_be_async as bisync;
+/// The async driver has no `trigger` and `poll`, so it keeps no trigger state.
+pub(crate) type Triggered = PhantomData<bool>;
+#[cfg(test)]
+pub(crate) use embassy_futures::block_on;
+#[cfg(test)]
+use embedded_hal_mock::eh1::digital::{State, Transaction as PinTransaction};
+
+use crate::error::private::Fault;
+
+pub use self::array::MprArray;
+pub use self::differential::Differential;
+pub use self::driver::Mpr;
+pub use self::measurement::PendingMeasurement;
+pub use self::power::PoweredMpr;
+pub use self::scan::scan;
+pub use self::stream::Stream;
+
+#[path = "array.rs"]
+mod array;
+#[path = "differential.rs"]
+mod differential;
+#[path = "driver.rs"]
+mod driver;
+#[path = "interface.rs"]
+mod interface;
+#[path = "measurement.rs"]
+mod measurement;
+#[path = "power.rs"]
+mod power;
+#[path = "scan.rs"]
+mod scan;
+#[path = "stream.rs"]
+mod stream;
+
+async fn wait_for_eoc<E: Eoc, D: DelayNs>(eoc: &mut E, mut delay: D, timeout_ms: Option<u32>) -> Result<(), Fault> {
+    match timeout_ms {
+        Some(timeout_ms) => match select(eoc.wait_for_high(), delay.delay_ms(timeout_ms)).await {
+            Either::First(result) => result.map_err(|_| Fault::Pin),
+            Either::Second(_) => Err(Fault::Timeout)
+        },
+        None => eoc.wait_for_high().await.map_err(|_| Fault::Pin)
+    }
+}
+
+/// Pin mock expectation for an EOC line that is already high.
+#[cfg(test)]
+pub(crate) fn eoc_high() -> PinTransaction {
+    PinTransaction::wait_for_state(State::High)
+}
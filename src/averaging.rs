@@ -0,0 +1,134 @@
+use crate::Reading;
+
+/// Mean of several conversions, with statistics of the raw counts that were kept.
+pub struct AveragedReading {
+    /// Reading built from the mean raw count.
+    pub reading: Reading,
+    /// Number of conversions with a valid status byte.
+    pub samples: u16,
+    pub min: u32,
+    pub max: u32,
+    /// Sample standard deviation in counts; 0 when only one sample was kept.
+    pub std_dev: f32,
+}
+
+/// Accumulates raw counts. Until a valid sample arrives, the most recent status error is kept so
+/// it can be reported if none of the conversions are valid.
+pub(crate) enum Samples<E> {
+    Invalid(E),
+    Valid(Stats)
+}
+impl<E> Samples<E> {
+    pub(crate) fn new(first: Result<u32, E>) -> Self {
+        match first {
+            Ok(raw_data) => Samples::Valid(Stats::new(raw_data)),
+            Err(e) => Samples::Invalid(e)
+        }
+    }
+
+    pub(crate) fn push(&mut self, sample: Result<u32, E>) {
+        match (self, sample) {
+            (Samples::Valid(stats), Ok(raw_data)) => stats.push(raw_data),
+            (Samples::Valid(_), Err(_)) => {},
+            (samples, sample) => *samples = Samples::new(sample)
+        }
+    }
+
+    /// Builds the result, converting the mean raw count with `reading`.
+    pub(crate) fn finish(self, reading: impl FnOnce(u32) -> Reading) -> Result<AveragedReading, E> {
+        match self {
+            Samples::Valid(stats) => Ok(stats.finish(reading)),
+            Samples::Invalid(e) => Err(e)
+        }
+    }
+}
+
+pub(crate) struct Stats {
+    count: u16,
+    sum: u64,
+    sum_squares: u128,
+    min: u32,
+    max: u32,
+}
+impl Stats {
+    fn new(raw_data: u32) -> Self {
+        let mut stats = Self { count: 0, sum: 0, sum_squares: 0, min: raw_data, max: raw_data };
+        stats.push(raw_data);
+        stats
+    }
+
+    fn push(&mut self, raw_data: u32) {
+        self.count += 1;
+        self.sum += raw_data as u64;
+        self.sum_squares += raw_data as u128 * raw_data as u128;
+        self.min = self.min.min(raw_data);
+        self.max = self.max.max(raw_data);
+    }
+
+    fn finish(&self, reading: impl FnOnce(u32) -> Reading) -> AveragedReading {
+        let count = self.count as u64;
+        let mean = (self.sum + count / 2) / count;
+        AveragedReading {
+            reading: reading(mean as u32),
+            samples: self.count,
+            min: self.min,
+            max: self.max,
+            std_dev: self.std_dev(),
+        }
+    }
+
+    fn std_dev(&self) -> f32 {
+        if self.count < 2 {
+            return 0.0
+        }
+        let count = self.count as u128;
+        let sum = self.sum as u128;
+        // n * sum(x^2) - sum(x)^2 == n * (n - 1) * variance, exact in integers
+        let scaled_variance = count * self.sum_squares - sum * sum;
+        // 8 fractional bits are kept through the integer square root
+        let variance_q16 = (scaled_variance << 16) / (count * (count - 1));
+        variance_q16.isqrt() as f32 / 256.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use crate::ZeroOffset;
+    use crate::sim::test_config;
+    use super::*;
+
+    fn reading(raw_data: u32) -> Reading {
+        test_config().reading(raw_data, ZeroOffset::default())
+    }
+
+    #[test]
+    fn finish_statistics_ok() {
+        let mut samples: Samples<()> = Samples::new(Err(()));
+        for raw_data in [8_388_600, 8_388_604, 8_388_608, 8_388_612, 8_388_616] {
+            samples.push(Ok(raw_data));
+        }
+        samples.push(Err(()));
+        let averaged = samples.finish(reading).ok().unwrap();
+        assert_eq!(averaged.reading.raw_data, 8_388_608);
+        assert_eq!(averaged.samples, 5);
+        assert_eq!(averaged.min, 8_388_600);
+        assert_eq!(averaged.max, 8_388_616);
+        assert!(relative_eq!(averaged.std_dev, 6.3246, epsilon = 1e-2));
+    }
+
+    #[test]
+    fn finish_single_sample_ok() {
+        let samples: Samples<()> = Samples::new(Ok(1_000));
+        let averaged = samples.finish(reading).ok().unwrap();
+        assert_eq!(averaged.reading.raw_data, 1_000);
+        assert_eq!(averaged.std_dev, 0.0);
+    }
+
+    #[test]
+    fn finish_no_valid_samples() {
+        let mut samples: Samples<u8> = Samples::new(Err(1));
+        samples.push(Err(2));
+        assert_eq!(samples.finish(reading).err(), Some(2));
+    }
+}
@@ -0,0 +1,212 @@
+//! Blocking driver built on `embedded_hal`.
+//!
+//! Shares its sources with `asynch`; only the HAL traits, the EOC wait and the `trigger` state
+//! differ.
+
+pub(crate) use embedded_hal::delay::DelayNs;
+pub(crate) use embedded_hal::digital::InputPin as Eoc;
+pub(crate) use embedded_hal::i2c::I2c;
+pub(crate) use embedded_hal::spi::SpiDevice;
+pub(crate) use maybe_async::must_be_sync as bisync;
+/// Set by `trigger` and cleared when `poll` or `poll_eoc` takes the conversion.
+pub(crate) type Triggered = bool;
+
+#[cfg(test)]
+use embedded_hal_mock::eh1::digital::{State, Transaction as PinTransaction};
+
+use crate::error::private::Fault;
+use crate::{Clock, Reading};
+use self::interface::Interface;
+
+pub use self::array::MprArray;
+pub use self::differential::Differential;
+pub use self::driver::Mpr;
+pub use self::measurement::PendingMeasurement;
+pub use self::power::PoweredMpr;
+pub use self::scan::scan;
+pub use self::stream::Stream;
+
+// the same sources as `asynch`, compiled again with the blocking traits
+#[allow(clippy::duplicate_mod)]
+#[path = "array.rs"]
+mod array;
+#[allow(clippy::duplicate_mod)]
+#[path = "differential.rs"]
+mod differential;
+#[allow(clippy::duplicate_mod)]
+#[path = "driver.rs"]
+mod driver;
+#[allow(clippy::duplicate_mod)]
+#[path = "interface.rs"]
+mod interface;
+#[allow(clippy::duplicate_mod)]
+#[path = "measurement.rs"]
+mod measurement;
+#[allow(clippy::duplicate_mod)]
+#[path = "power.rs"]
+mod power;
+#[allow(clippy::duplicate_mod)]
+#[path = "scan.rs"]
+mod scan;
+#[allow(clippy::duplicate_mod)]
+#[path = "stream.rs"]
+mod stream;
+
+const EOC_POLL_INTERVAL_US: u32 = 100;
+
+fn wait_for_eoc<E: Eoc, D: DelayNs>(eoc: &mut E, mut delay: D, timeout_ms: Option<u32>) -> Result<(), Fault> {
+    let timeout_us = timeout_ms.map(|ms| ms.saturating_mul(1_000));
+    let mut elapsed_us: u32 = 0;
+    while !eoc.is_high().map_err(|_| Fault::Pin)? {
+        if timeout_us.is_some_and(|timeout_us| elapsed_us >= timeout_us) {
+            return Err(Fault::Timeout)
+        }
+        delay.delay_us(EOC_POLL_INTERVAL_US);
+        elapsed_us = elapsed_us.saturating_add(EOC_POLL_INTERVAL_US);
+    }
+    Ok(())
+}
+
+/// Non-blocking measurement for main loops that can neither block nor run an executor.
+impl<I: Interface> Mpr<I> {
+    /// Exits standby to start a conversion; follow with `poll` or `poll_eoc`.
+    pub fn trigger(&mut self) -> Result<(), I::Error> {
+        self.exit_standby()?;
+        self.triggered = true;
+        Ok(())
+    }
+
+    /// Returns `WouldBlock` while the status byte reports busy, then reads the conversion
+    /// started by `trigger`.
+    ///
+    /// Each conversion is returned once; polling again before the next `trigger` returns the
+    /// `NotTriggered` error instead of the stale conversion.
+    pub fn poll(&mut self) -> nb::Result<Reading, I::Error> {
+        self.check_triggered()?;
+        if self.status()?.is_busy() {
+            return Err(nb::Error::WouldBlock)
+        }
+        self.triggered = false;
+        Ok(self.read()?)
+    }
+
+    /// Like `poll`, but checks the EOC pin instead of the status byte, saving a bus transaction
+    /// per call.
+    pub fn poll_eoc<E: Eoc>(&mut self, eoc: &mut E) -> nb::Result<Reading, I::Error> {
+        self.check_triggered()?;
+        if !eoc.is_high().map_err(|_| I::Error::from(Fault::Pin))? {
+            return Err(nb::Error::WouldBlock)
+        }
+        self.triggered = false;
+        Ok(self.read()?)
+    }
+
+    fn check_triggered(&self) -> Result<(), I::Error> {
+        if !self.triggered {
+            return Err(Fault::NotTriggered.into())
+        }
+        Ok(())
+    }
+}
+
+impl<I: Interface, C: Clock, D: DelayNs> Iterator for Stream<'_, I, C, D> {
+    type Item = Result<Reading, I::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_reading())
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn block_on<T>(result: T) -> T {
+    result
+}
+
+/// Pin mock expectation for an EOC line that is already high.
+#[cfg(test)]
+pub(crate) fn eoc_high() -> PinTransaction {
+    PinTransaction::get(State::High)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use embedded_hal_mock::eh1::digital::Mock as PinMock;
+    use std::vec;
+    use crate::{MprI2cError, MprSpiError};
+    use crate::sim::{test_config, SimFaults, SimMpr};
+    use super::*;
+
+    #[test]
+    fn stream_iterator_ok() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(sensor.stream(10_000, sim.delay(), sim.delay()).take(4).filter(Result::is_ok).count(), 4);
+        assert_eq!(sim.now_us(), 40_000);
+    }
+
+    #[test]
+    fn trigger_poll_i2c_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        sensor.trigger().unwrap();
+        assert!(matches!(sensor.poll(), Err(nb::Error::WouldBlock)));
+        sim.advance_us(4_000);
+        assert!(matches!(sensor.poll(), Err(nb::Error::WouldBlock)));
+        sim.advance_us(1_000);
+        let reading = sensor.poll().unwrap();
+        assert!(relative_eq!(reading.psi(), 5.0, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn poll_not_triggered() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert!(matches!(sensor.poll(), Err(nb::Error::Other(MprI2cError::NotTriggered))));
+        sensor.trigger().unwrap();
+        sim.advance_us(5_000);
+        assert!(sensor.poll().is_ok());
+        assert!(matches!(sensor.poll(), Err(nb::Error::Other(MprI2cError::NotTriggered))));
+        assert!(matches!(sensor.poll_eoc(&mut sim.eoc_pin()), Err(nb::Error::Other(MprI2cError::NotTriggered))));
+    }
+
+    #[test]
+    fn trigger_poll_spi_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_spi(&sim);
+        sensor.trigger().unwrap();
+        assert!(matches!(sensor.poll(), Err(nb::Error::WouldBlock)));
+        sim.advance_us(5_000);
+        assert!(relative_eq!(nb::block!(sensor.poll()).unwrap().psi(), 5.0, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn poll_errors() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_spi(&sim);
+        sim.set_faults(SimFaults { math_saturation: true, ..Default::default() });
+        sensor.trigger().unwrap();
+        sim.advance_us(5_000);
+        assert!(matches!(sensor.poll(), Err(nb::Error::Other(MprSpiError::MathSaturation))));
+        sim.set_faults(SimFaults { nak: true, ..Default::default() });
+        assert!(matches!(sensor.trigger(), Err(MprSpiError::Spi(_))));
+    }
+
+    #[test]
+    fn trigger_poll_eoc_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let mut eoc = PinMock::new(&vec![
+            PinTransaction::get(State::Low),
+            PinTransaction::get(State::High)
+        ]);
+        sensor.trigger().unwrap();
+        assert!(matches!(sensor.poll_eoc(&mut eoc), Err(nb::Error::WouldBlock)));
+        sim.advance_us(5_000);
+        assert!(relative_eq!(sensor.poll_eoc(&mut eoc).unwrap().psi(), 5.0, epsilon = 1e-4));
+        eoc.done();
+    }
+}
@@ -0,0 +1,329 @@
+use crate::MprConfig;
+use crate::typedefs::{div_round, to_milli, MAX_COUNTS};
+
+/// Maximum number of points in a `Calibration` table.
+pub const MAX_CALIBRATION_POINTS: usize = 8;
+
+/// Raw count offset subtracted from every conversion, captured by `Mpr::tare` at a known zero
+/// pressure (ambient for gauge parts).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ZeroOffset {
+    counts: i32
+}
+impl ZeroOffset {
+    pub fn new(counts: i32) -> Self {
+        Self { counts }
+    }
+
+    /// Offset that makes `raw_data` read as zero pressure in the range and calibration of
+    /// `config`.
+    pub fn capture(raw_data: u32, config: &MprConfig) -> Self {
+        let mut zero_decicounts = config.ideal_decicounts(0);
+        if let Some(calibration) = &config.calibration {
+            zero_decicounts = calibration.raw_decicounts(zero_decicounts);
+        }
+        Self { counts: div_round(raw_data as i64 * 10 - zero_decicounts, 10) as i32 }
+    }
+
+    pub fn counts(&self) -> i32 {
+        self.counts
+    }
+
+    /// Subtracts the offset from `raw_data`, saturating to the 24-bit output range, e.g. before
+    /// `MprConfig::pascals`.
+    pub fn apply(&self, raw_data: u32) -> u32 {
+        (raw_data as i64 - self.counts as i64).clamp(0, 0x00ff_ffff) as u32
+    }
+
+    /// Little-endian representation for non-volatile storage.
+    pub fn to_bytes(&self) -> [u8; 4] {
+        self.counts.to_le_bytes()
+    }
+
+    /// Restores an offset saved with `to_bytes`.
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self { counts: i32::from_le_bytes(bytes) }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CalibrationError {
+    TooFewPoints,
+    TooManyPoints,
+    /// Raw counts and reference pressures must both strictly increase.
+    NotMonotonic,
+    BufferTooSmall,
+    InvalidData,
+    /// The config range is empty or reversed.
+    InvalidRange,
+    /// A raw count, or the ideal count of a reference pressure, falls outside the 24-bit output.
+    CountsOutOfRange
+}
+
+/// Piecewise-linear correction fitted against a reference gauge, applied to raw counts before
+/// conversion. With two points this is a gain and offset correction.
+///
+/// Conversions between points are interpolated and those outside the table are extrapolated from
+/// the nearest segment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Calibration {
+    // (raw, ideal) pairs in tenths of a count, sorted by raw
+    points: [(i32, i32); MAX_CALIBRATION_POINTS],
+    len: u8
+}
+impl Calibration {
+    /// Size of the largest `to_bytes` output.
+    pub const MAX_BYTES: usize = 1 + MAX_CALIBRATION_POINTS * 8;
+
+    /// Fits a gain and offset from two `(raw_data, reference_pressure)` pairs, with the reference
+    /// pressure in the native unit of `config`.
+    pub fn two_point(low: (u32, f32), high: (u32, f32), config: &MprConfig) -> Result<Self, CalibrationError> {
+        Self::fit(&[low, high], config)
+    }
+
+    /// Fits a table from 2 to `MAX_CALIBRATION_POINTS` `(raw_data, reference_pressure)` pairs, in
+    /// any order, with the reference pressure in the native unit of `config`.
+    pub fn fit(points: &[(u32, f32)], config: &MprConfig) -> Result<Self, CalibrationError> {
+        Self::fit_with(points.len(), |i| (points[i].0, to_milli(points[i].1)), config)
+    }
+
+    /// Float-free `fit`; reference pressures are thousandths of the native unit.
+    pub fn fit_milli(points: &[(u32, i32)], config: &MprConfig) -> Result<Self, CalibrationError> {
+        Self::fit_with(points.len(), |i| points[i], config)
+    }
+
+    /// Serializes to `buf` as a point count followed by little-endian raw and ideal decicounts,
+    /// returning the number of bytes written.
+    pub fn to_bytes(&self, buf: &mut [u8]) -> Result<usize, CalibrationError> {
+        let size = 1 + self.points().len() * 8;
+        if buf.len() < size {
+            return Err(CalibrationError::BufferTooSmall)
+        }
+        buf[0] = self.len;
+        for (chunk, (raw, ideal)) in buf[1..size].chunks_exact_mut(8).zip(self.points()) {
+            chunk[..4].copy_from_slice(&raw.to_le_bytes());
+            chunk[4..].copy_from_slice(&ideal.to_le_bytes());
+        }
+        Ok(size)
+    }
+
+    /// Restores a table saved with `to_bytes`; trailing bytes are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CalibrationError> {
+        let len = *bytes.first().ok_or(CalibrationError::InvalidData)? as usize;
+        if !(2..=MAX_CALIBRATION_POINTS).contains(&len) || bytes.len() < 1 + len * 8 {
+            return Err(CalibrationError::InvalidData)
+        }
+        let mut calibration = Self { points: [(0, 0); MAX_CALIBRATION_POINTS], len: len as u8 };
+        for (point, chunk) in calibration.points.iter_mut().zip(bytes[1..].chunks_exact(8)).take(len) {
+            let raw = i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            let ideal = i32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+            if !in_range(raw as i64) || !in_range(ideal as i64) {
+                return Err(CalibrationError::InvalidData)
+            }
+            *point = (raw, ideal);
+        }
+        if !calibration.is_monotonic() {
+            return Err(CalibrationError::InvalidData)
+        }
+        Ok(calibration)
+    }
+
+    /// Corrected tenths of a count for `raw_data`.
+    pub(crate) fn ideal_decicounts(&self, raw_data: u32) -> i64 {
+        interpolate(self.points(), raw_data as i64 * 10, false)
+    }
+
+    /// Inverse of `ideal_decicounts`, in tenths of a count.
+    pub(crate) fn raw_decicounts(&self, ideal_decicounts: i64) -> i64 {
+        interpolate(self.points(), ideal_decicounts, true)
+    }
+
+    fn fit_with(
+        len: usize,
+        point: impl Fn(usize) -> (u32, i32),
+        config: &MprConfig
+    ) -> Result<Self, CalibrationError> {
+        if len < 2 {
+            return Err(CalibrationError::TooFewPoints)
+        }
+        if len > MAX_CALIBRATION_POINTS {
+            return Err(CalibrationError::TooManyPoints)
+        }
+        if config.pressure_max <= config.pressure_min {
+            return Err(CalibrationError::InvalidRange)
+        }
+        let mut calibration = Self { points: [(0, 0); MAX_CALIBRATION_POINTS], len: len as u8 };
+        for i in 0..len {
+            let (raw_data, pressure_milli) = point(i);
+            let ideal = config.ideal_decicounts(pressure_milli);
+            if raw_data > MAX_COUNTS || !in_range(ideal) {
+                return Err(CalibrationError::CountsOutOfRange)
+            }
+            calibration.points[i] = ((raw_data * 10) as i32, ideal as i32);
+        }
+        calibration.points[..len].sort_unstable_by_key(|(raw, _)| *raw);
+        if !calibration.is_monotonic() {
+            return Err(CalibrationError::NotMonotonic)
+        }
+        Ok(calibration)
+    }
+
+    fn points(&self) -> &[(i32, i32)] {
+        &self.points[..self.len as usize]
+    }
+
+    fn is_monotonic(&self) -> bool {
+        self.points().windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1)
+    }
+}
+
+/// Whether tenths of a count are within the 24-bit output.
+fn in_range(decicounts: i64) -> bool {
+    (0..=MAX_COUNTS as i64 * 10).contains(&decicounts)
+}
+
+/// Linear interpolation over `(raw, ideal)` points sorted by both, mapping ideal to raw when
+/// `inverse` is set. Values outside the table are extrapolated from the end segments.
+fn interpolate(points: &[(i32, i32)], x: i64, inverse: bool) -> i64 {
+    let point = |i: usize| {
+        let (raw, ideal) = points[i];
+        if inverse { (ideal as i64, raw as i64) } else { (raw as i64, ideal as i64) }
+    };
+    // end of the first segment that extends past `x`, or of the last segment
+    let end = (1..points.len() - 1).find(|&i| x < point(i).0).unwrap_or(points.len() - 1);
+    let ((x0, y0), (x1, y1)) = (point(end - 1), point(end));
+    y0 + div_round((x - x0) * (y1 - y0), x1 - x0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PressureUnit, TransferFunction};
+    use crate::sim::test_config;
+    use super::*;
+
+    #[test]
+    fn capture_gauge_ok() {
+        let config = test_config();
+        assert_eq!(ZeroOffset::capture(1_677_722, &config).counts(), 0);
+        assert_eq!(ZeroOffset::capture(1_677_822, &config).counts(), 100);
+        assert_eq!(ZeroOffset::capture(1_677_622, &config).counts(), -100);
+    }
+
+    #[test]
+    fn capture_compound_ok() {
+        let config = MprConfig::new(-1.0, 1.0, PressureUnit::Psi, TransferFunction::A);
+        assert_eq!(ZeroOffset::capture(8_388_608 + 250, &config).counts(), 250);
+    }
+
+    #[test]
+    fn apply_saturates() {
+        assert_eq!(ZeroOffset::new(100).apply(1_000), 900);
+        assert_eq!(ZeroOffset::new(100).apply(50), 0);
+        assert_eq!(ZeroOffset::new(-100).apply(0x00ff_fff0), 0x00ff_ffff);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        for counts in [0, 1, -1, 123_456, -8_388_608] {
+            let offset = ZeroOffset::new(counts);
+            assert_eq!(ZeroOffset::from_bytes(offset.to_bytes()), offset);
+        }
+        assert_eq!(ZeroOffset::new(-2).to_bytes(), [0xfe, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn two_point_ok() {
+        let calibration = Calibration::two_point((1_700_000, 0.0), (15_000_000, 25.0), &test_config()).unwrap();
+        let config = test_config().with_calibration(calibration);
+        assert_eq!(config.pressure_micro(1_700_000), 0);
+        assert_eq!(config.pressure_micro(8_350_000), 12_500_000);
+        assert_eq!(config.pressure_micro(15_000_000), 25_000_000);
+        // extrapolated past the reference points
+        assert_eq!(config.pressure_micro(15_000_000 + 532_000), 26_000_000);
+    }
+
+    #[test]
+    fn fit_piecewise_ok() {
+        let points = [(15_000_000, 25.0), (1_700_000, 0.0), (9_000_000, 10.0)];
+        let config = test_config().with_calibration(Calibration::fit(&points, &test_config()).unwrap());
+        assert_eq!(config.pressure_micro(5_350_000), 5_000_000);
+        assert_eq!(config.pressure_micro(12_000_000), 17_500_000);
+        assert_eq!(config.pressure_micro(1_700_000 - 365_000), -500_000);
+    }
+
+    #[test]
+    fn fit_milli_matches_fit() {
+        let config = test_config();
+        assert_eq!(
+            Calibration::fit_milli(&[(1_700_000, 0), (15_000_000, 25_000)], &config),
+            Calibration::fit(&[(1_700_000, 0.0), (15_000_000, 25.0)], &config)
+        );
+    }
+
+    #[test]
+    fn fit_invalid() {
+        let config = test_config();
+        assert_eq!(Calibration::fit(&[(1_700_000, 0.0)], &config), Err(CalibrationError::TooFewPoints));
+        assert_eq!(Calibration::fit(&[(0, 0.0); 9], &config), Err(CalibrationError::TooManyPoints));
+        assert_eq!(
+            Calibration::fit(&[(1_700_000, 0.0), (1_700_000, 1.0)], &config),
+            Err(CalibrationError::NotMonotonic)
+        );
+        assert_eq!(
+            Calibration::fit(&[(1_700_000, 1.0), (15_000_000, 0.0)], &config),
+            Err(CalibrationError::NotMonotonic)
+        );
+        assert_eq!(
+            Calibration::fit(&[(1_700_000, 0.0), (0x100_0000, 1.0)], &config),
+            Err(CalibrationError::CountsOutOfRange)
+        );
+        for reference in [1_000.0, 10_000.0, 172_369.0] {
+            assert_eq!(
+                Calibration::fit(&[(1_700_000, 0.0), (15_000_000, reference)], &config),
+                Err(CalibrationError::CountsOutOfRange)
+            );
+        }
+        let empty = MprConfig::new(5.0, 5.0, PressureUnit::Psi, TransferFunction::A);
+        assert_eq!(
+            Calibration::two_point((1_700_000, 5.0), (15_000_000, 5.0), &empty),
+            Err(CalibrationError::InvalidRange)
+        );
+    }
+
+    #[test]
+    fn capture_empty_range() {
+        let config = MprConfig::new(5.0, 5.0, PressureUnit::Psi, TransferFunction::A);
+        assert_eq!(ZeroOffset::capture(1_677_822, &config).counts(), 100);
+    }
+
+    #[test]
+    fn calibration_bytes_round_trip() {
+        let points = [(1_700_000, 0.0), (9_000_000, 10.0), (15_000_000, 25.0)];
+        let calibration = Calibration::fit(&points, &test_config()).unwrap();
+        let mut buf = [0u8; Calibration::MAX_BYTES];
+        assert_eq!(calibration.to_bytes(&mut buf), Ok(25));
+        assert_eq!(Calibration::from_bytes(&buf), Ok(calibration));
+        assert_eq!(calibration.to_bytes(&mut buf[..24]), Err(CalibrationError::BufferTooSmall));
+    }
+
+    #[test]
+    fn calibration_from_bytes_invalid() {
+        assert_eq!(Calibration::from_bytes(&[]), Err(CalibrationError::InvalidData));
+        assert_eq!(Calibration::from_bytes(&[0xff; Calibration::MAX_BYTES]), Err(CalibrationError::InvalidData));
+        assert_eq!(Calibration::from_bytes(&[2, 0, 0, 0, 0, 0, 0, 0, 0]), Err(CalibrationError::InvalidData));
+        assert_eq!(Calibration::from_bytes(&[2; 17]), Err(CalibrationError::InvalidData));
+        let mut bytes = [0u8; 17];
+        bytes[0] = 2;
+        for (chunk, value) in bytes[1..].chunks_exact_mut(4).zip([i32::MIN, i32::MIN, i32::MAX, i32::MAX]) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        assert_eq!(Calibration::from_bytes(&bytes), Err(CalibrationError::InvalidData));
+    }
+
+    #[test]
+    fn capture_calibrated_ok() {
+        let calibration = Calibration::two_point((1_700_000, 0.0), (15_000_000, 25.0), &test_config()).unwrap();
+        let config = test_config().with_calibration(calibration);
+        assert_eq!(ZeroOffset::capture(1_700_100, &config).counts(), 100);
+    }
+}
@@ -0,0 +1,4 @@
+/// Monotonic time source used to pace a `Stream`, e.g. `embassy_time::Instant::now().as_micros()`.
+pub trait Clock {
+    fn now_us(&mut self) -> u64;
+}
@@ -0,0 +1,79 @@
+use crate::flow::DifferentialError;
+use super::{bisync, DelayNs};
+use super::driver::{Mpr, EXIT_STANDBY_DELAY_MS};
+use super::interface::Interface;
+
+/// Two sensors on the high and low pressure taps, read as one differential.
+///
+/// Both conversions are started before waiting, so they are taken within one bus transaction
+/// of each other. Tare both sensors at zero flow to cancel their offsets.
+pub struct Differential<H, L> {
+    high: Mpr<H>,
+    low: Mpr<L>
+}
+impl<H: Interface, L: Interface> Differential<H, L> {
+    pub fn new(high: Mpr<H>, low: Mpr<L>) -> Self {
+        Self { high, low }
+    }
+
+    /// Returns the sensors.
+    pub fn release(self) -> (Mpr<H>, Mpr<L>) {
+        (self.high, self.low)
+    }
+
+    /// Starts both conversions, waits and returns high minus low pressure in pascals.
+    #[bisync]
+    pub async fn read_pa<D: DelayNs>(&mut self, mut delay: D) -> Result<f32, DifferentialError<H::Error, L::Error>> {
+        self.high.exit_standby().await.map_err(DifferentialError::High)?;
+        self.low.exit_standby().await.map_err(DifferentialError::Low)?;
+        delay.delay_ms(EXIT_STANDBY_DELAY_MS).await;
+        let high = self.high.read().await.map_err(DifferentialError::High)?;
+        let low = self.low.read().await.map_err(DifferentialError::Low)?;
+        Ok((high.kpa() - low.kpa()) * 1_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use embedded_hal::i2c::ErrorKind as I2cErrorKind;
+    use crate::{MprI2cError, MprSpiError};
+    use crate::sim::{test_config, SimClock, SimFaults, SimMpr};
+    use super::super::block_on;
+    use super::*;
+
+    #[test]
+    fn differential_read_pa_ok() {
+        let clock = SimClock::new();
+        let high_sim = SimMpr::with_clock(test_config(), &clock);
+        let low_sim = SimMpr::with_clock(test_config(), &clock);
+        high_sim.set_pressure(15.0);
+        low_sim.set_pressure(14.0);
+        let high = Mpr::sim_i2c(&high_sim);
+        let low = Mpr::sim_spi(&low_sim);
+        let mut differential = Differential::new(high, low);
+        let differential_pa = block_on(differential.read_pa(high_sim.delay())).unwrap();
+        assert!(relative_eq!(differential_pa, 6_894.757, epsilon = 0.5));
+        assert_eq!(clock.now_us(), EXIT_STANDBY_DELAY_MS as u64 * 1_000);
+    }
+
+    #[test]
+    fn differential_read_pa_errors() {
+        let clock = SimClock::new();
+        let high_sim = SimMpr::with_clock(test_config(), &clock);
+        let low_sim = SimMpr::with_clock(test_config(), &clock);
+        let high = Mpr::sim_i2c(&high_sim);
+        let low = Mpr::sim_spi(&low_sim);
+        let mut differential = Differential::new(high, low);
+        low_sim.set_faults(SimFaults { math_saturation: true, ..Default::default() });
+        assert_eq!(
+            block_on(differential.read_pa(high_sim.delay())),
+            Err(DifferentialError::Low(MprSpiError::MathSaturation))
+        );
+        high_sim.set_faults(SimFaults { nak: true, ..Default::default() });
+        assert!(matches!(
+            block_on(differential.read_pa(high_sim.delay())),
+            Err(DifferentialError::High(MprI2cError::I2c(I2cErrorKind::NoAcknowledge(_))))
+        ));
+    }
+}
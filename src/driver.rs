@@ -1,32 +1,27 @@
-#[cfg(not(feature = "sync"))]
-use embedded_hal_async::delay::DelayNs;
-#[cfg(not(feature = "sync"))]
-use embedded_hal_async::i2c::I2c;
-#[cfg(not(feature = "sync"))]
-use embedded_hal_async::spi::SpiDevice;
-
-
-#[cfg(feature = "sync")]
-use embedded_hal::delay::DelayNs;
-#[cfg(feature = "sync")]
-use embedded_hal::i2c::I2c;
-#[cfg(feature = "sync")]
-use embedded_hal::spi::SpiDevice;
-
-use crate::interface::{I2cInterface, Interface, MprI2cError, MprSpiError, SpiInterface};
-use crate::{MprConfig, Reading};
+use crate::averaging::{AveragedReading, Samples};
+use crate::error::{MprI2cError, MprSpiError};
+use crate::error::private::Fault;
+use crate::{Clock, MprConfig, RangePolicy, RangeStatus, Reading, VALID_I2C_ADDRESSES, ZeroOffset};
 use crate::registers::Status;
+use super::{bisync, wait_for_eoc, DelayNs, Eoc, I2c, SpiDevice, Triggered};
+use super::interface::{I2cInterface, Interface, SpiInterface};
+use super::measurement::PendingMeasurement;
+use super::stream::Stream;
+#[cfg(test)]
+use crate::sim::{test_config, SimI2c, SimMpr, SimSpi};
 
 // TODO change to EXIT_STANDBY_MIN_DELAY_MS: u32 = 5;
-const EXIT_STANDBY_DELAY_MS: u32 = 10;
+pub(crate) const EXIT_STANDBY_DELAY_MS: u32 = 10;
 const OUTPUT_MEASUREMENT_CMD: [u8; 3] = [0xaa, 0x00, 0x00];
-const VALID_I2C_ADDRESSES: [u8; 8] = [0x08, 0x18, 0x28, 0x38, 0x48, 0x58, 0x68, 0x78];
 
 /// MPR driver instance consisting of configuration and a I2C or SPI interface.
 #[derive(Debug)]
 pub struct Mpr<I> {
     config: MprConfig,
-    interface: I
+    interface: I,
+    range_policy: RangePolicy,
+    zero_offset: ZeroOffset,
+    pub(super) triggered: Triggered
 }
 
 /// Constructs a MPR driver instance using the I2C interface.
@@ -35,79 +30,787 @@ impl <I2C: I2c> Mpr<I2cInterface<I2C>> {
         if !VALID_I2C_ADDRESSES.contains(&address) {
             return Err(MprI2cError::InvalidAddress)
         }
-        Ok(Mpr { config, interface: I2cInterface::new(device, address) })
+        Ok(Mpr::with_interface(I2cInterface::new(device, address), config))
+    }
+
+    /// Returns the I2C device and configuration.
+    pub fn release(self) -> (I2C, MprConfig) {
+        (self.interface.release(), self.config)
     }
 }
 
 /// Constructs a MPR driver instance using the SPI interface.
 impl <SPI: SpiDevice> Mpr<SpiInterface<SPI>> {
     pub fn new_spi(device: SPI, config: MprConfig) -> Result<Mpr<SpiInterface<SPI>>, MprSpiError<SPI::Error>>  {
-        Ok(Mpr { config, interface: SpiInterface::new(device) })
+        Ok(Mpr::with_interface(SpiInterface::new(device), config))
+    }
+
+    /// Returns the SPI device and configuration.
+    pub fn release(self) -> (SPI, MprConfig) {
+        (self.interface.release(), self.config)
     }
 }
 
 impl <I: Interface>Mpr<I> {
+    fn with_interface(interface: I, config: MprConfig) -> Self {
+        Self {
+            config,
+            interface,
+            range_policy: RangePolicy::default(),
+            zero_offset: ZeroOffset::default(),
+            triggered: Triggered::default()
+        }
+    }
 
     /// Exits sensor standby mode and enters operating mode in preparation for measurement.
     ///
     /// App should delay >=5ms or wait for rising edge on EOC line after this returns and before
-    /// reading measurement data via any `read_raw*` method.
-    #[maybe_async::maybe_async]
-    pub async fn exit_standby(&mut self) -> Result<(), I::Error> {
+    /// reading measurement data via any `read_raw*` method. On SPI the status byte clocked out on
+    /// MISO during the command is returned; I2C requires a dedicated read, so `None` is returned.
+    #[bisync]
+    pub async fn exit_standby(&mut self) -> Result<Option<Status>, I::Error> {
         self.interface.write_reg(&OUTPUT_MEASUREMENT_CMD).await
-        // TODO should this return Status (first byte?) MISO on SPI, but a dedicated read on I2C...
     }
 
     /// Reads 24-bits of raw pressure data.
-    #[maybe_async::maybe_async]
+    ///
+    /// Fails if the status byte reports the sensor as not powered or busy (the data would be
+    /// stale), or flags a math saturation or integrity test failure.
+    ///
+    /// Low-level and unchecked: no conversion is started, and without a prior `exit_standby` and
+    /// wait the last conversion is returned again. Only `start_measurement` checks the order at
+    /// compile time.
+    #[bisync]
     pub async fn read_raw(&mut self) -> Result<u32, I::Error> {
-        let mut buf = [0u8; 4];
-        self.interface.read_reg(&mut buf).await?;
-
-        let status = Status::from_bits(buf[0]);
-        self.interface.validate_status(status)?;
-        Ok(((buf[1] as u32) << 16) + ((buf[2] as u32) << 8) + buf[3] as u32)
+        self.read_frame().await?
     }
 
     /// Exits standby, waits and then reads raw pressure data.
-    #[maybe_async::maybe_async]
-    pub async fn read_raw_with_delay<D: DelayNs>(&mut self, mut delay: D) -> Result<u32, I::Error> {
-        self.exit_standby().await?;
-        delay.delay_ms(EXIT_STANDBY_DELAY_MS).await;
-        self.read_raw().await
+    #[bisync]
+    pub async fn read_raw_with_delay<D: DelayNs>(&mut self, delay: D) -> Result<u32, I::Error> {
+        self.read_frame_with_delay(delay).await?
     }
 
-    /// Reads 24-bits of raw pressure data as a Reading.
-    #[maybe_async::maybe_async]
+    /// Reads 24-bits of raw pressure data as a Reading. Unchecked like `read_raw`.
+    #[bisync]
     pub async fn read(&mut self) -> Result<Reading, I::Error> {
         let raw_data = self.read_raw().await?;
-        Ok(Reading {
-            pressure_min: self.config.pressure_min as f32,
-            pressure_max: self.config.pressure_max as f32,
-            raw_data,
-            transfer_function: self.config.transfer_function
-        })
+        self.reading(raw_data)
     }
 
     /// Exits standby, waits and then reads raw pressure data as a Reading.
-    #[maybe_async::maybe_async]
-    pub async fn read_with_delay<D: DelayNs>(&mut self, mut delay: D) -> Result<Reading, I::Error> {
+    #[bisync]
+    pub async fn read_with_delay<D: DelayNs>(&mut self, delay: D) -> Result<Reading, I::Error> {
+        let raw_data = self.read_raw_with_delay(delay).await?;
+        self.reading(raw_data)
+    }
+
+    /// Exits standby, waits for the EOC pin to go high and then reads raw pressure data.
+    ///
+    /// `eoc` is awaited with `embedded_hal_async::digital::Wait` or, in the blocking driver,
+    /// polled with `embedded_hal::digital::InputPin`. Returns a timeout error if `timeout_ms`
+    /// elapses before end of conversion is signaled.
+    #[bisync]
+    pub async fn read_raw_with_eoc<E: Eoc, D: DelayNs>(
+        &mut self,
+        eoc: &mut E,
+        delay: D,
+        timeout_ms: Option<u32>
+    ) -> Result<u32, I::Error> {
+        self.read_frame_with_eoc(eoc, delay, timeout_ms).await?
+    }
+
+    /// Exits standby, waits for the EOC pin to go high and then reads raw pressure data as a
+    /// Reading.
+    #[bisync]
+    pub async fn read_with_eoc<E: Eoc, D: DelayNs>(
+        &mut self,
+        eoc: &mut E,
+        delay: D,
+        timeout_ms: Option<u32>
+    ) -> Result<Reading, I::Error> {
+        let raw_data = self.read_raw_with_eoc(eoc, delay, timeout_ms).await?;
+        self.reading(raw_data)
+    }
+
+    /// Exits standby, then polls the status byte every `poll_interval_us` until the busy flag
+    /// clears and reads raw pressure data.
+    ///
+    /// Returns a timeout error if the sensor is still busy after `timeout_ms`.
+    #[bisync]
+    pub async fn read_raw_with_polling<D: DelayNs>(
+        &mut self,
+        delay: D,
+        poll_interval_us: u32,
+        timeout_ms: u32
+    ) -> Result<u32, I::Error> {
+        self.read_frame_with_polling(delay, poll_interval_us, timeout_ms).await?
+    }
+
+    /// Exits standby, polls the busy flag and then reads raw pressure data as a Reading.
+    #[bisync]
+    pub async fn read_with_polling<D: DelayNs>(
+        &mut self,
+        delay: D,
+        poll_interval_us: u32,
+        timeout_ms: u32
+    ) -> Result<Reading, I::Error> {
+        let raw_data = self.read_raw_with_polling(delay, poll_interval_us, timeout_ms).await?;
+        self.reading(raw_data)
+    }
+
+    /// Takes `n` conversions (at least one), each after a fixed delay, and averages the raw data
+    /// of those with a valid status byte.
+    ///
+    /// Bus errors are returned immediately. If no conversion has a valid status, the last status
+    /// error is returned.
+    #[bisync]
+    pub async fn read_averaged<D: DelayNs>(&mut self, n: u16, delay: D) -> Result<AveragedReading, I::Error> {
+        let samples = self.samples_with_delay(n, delay).await?;
+        self.averaged(samples)
+    }
+
+    /// Like `read_averaged`, but polls the busy flag instead of waiting a fixed delay for each
+    /// conversion.
+    #[bisync]
+    pub async fn read_averaged_with_polling<D: DelayNs>(
+        &mut self,
+        n: u16,
+        mut delay: D,
+        poll_interval_us: u32,
+        timeout_ms: u32
+    ) -> Result<AveragedReading, I::Error> {
+        let mut samples = Samples::new(
+            self.read_frame_with_polling(&mut delay, poll_interval_us, timeout_ms).await?
+        );
+        for _ in 1..n {
+            samples.push(self.read_frame_with_polling(&mut delay, poll_interval_us, timeout_ms).await?);
+        }
+        self.averaged(samples)
+    }
+
+    /// Like `read_averaged`, but waits for the EOC pin instead of a fixed delay for each
+    /// conversion.
+    #[bisync]
+    pub async fn read_averaged_with_eoc<E: Eoc, D: DelayNs>(
+        &mut self,
+        n: u16,
+        eoc: &mut E,
+        mut delay: D,
+        timeout_ms: Option<u32>
+    ) -> Result<AveragedReading, I::Error> {
+        let mut samples = Samples::new(self.read_frame_with_eoc(eoc, &mut delay, timeout_ms).await?);
+        for _ in 1..n {
+            samples.push(self.read_frame_with_eoc(eoc, &mut delay, timeout_ms).await?);
+        }
+        self.averaged(samples)
+    }
+
+    /// Exits standby and returns a token that must be resolved by waiting for the conversion
+    /// before the Reading can be taken.
+    ///
+    /// The driver stays borrowed until then, so no other call can be made on it before the
+    /// conversion is finished and read.
+    #[bisync]
+    pub async fn start_measurement(&mut self) -> Result<PendingMeasurement<'_, I>, I::Error> {
         self.exit_standby().await?;
-        delay.delay_ms(EXIT_STANDBY_DELAY_MS).await;
-        let raw_data = self.read_raw().await?;
-        Ok(Reading {
-            pressure_min: self.config.pressure_min as f32,
-            pressure_max: self.config.pressure_max as f32,
-            raw_data,
-            transfer_function: self.config.transfer_function
-        })
+        Ok(PendingMeasurement::new(self))
+    }
+
+    /// Starts continuous sampling every `period_us`, paced by `clock` and `delay`.
+    ///
+    /// `period_us` must cover the conversion time (~5ms), otherwise reads report busy.
+    pub fn stream<C: Clock, D: DelayNs>(&mut self, period_us: u32, clock: C, delay: D) -> Stream<'_, I, C, D> {
+        Stream::new(self, period_us, clock, delay)
+    }
+
+    /// Averages `n` conversions taken at a known zero pressure (ambient for gauge parts) and
+    /// stores the resulting offset, which is applied to every subsequent Reading.
+    ///
+    /// Raw reads are not affected. The returned offset can be saved with `ZeroOffset::to_bytes`
+    /// and restored with `set_zero_offset` after a reset. The range policy is not applied, so
+    /// conversions slightly below the transfer function limits can be tared.
+    #[bisync]
+    pub async fn tare<D: DelayNs>(&mut self, n: u16, delay: D) -> Result<ZeroOffset, I::Error> {
+        let samples = self.samples_with_delay(n, delay).await?;
+        let averaged = samples.finish(|raw_data| self.config.reading(raw_data, ZeroOffset::default()))?;
+        self.zero_offset = ZeroOffset::capture(averaged.reading.raw_data, &self.config);
+        Ok(self.zero_offset)
+    }
+
+    pub fn config(&self) -> MprConfig {
+        self.config
+    }
+
+    /// Replaces the configuration, e.g. after reading the part number of the fitted sensor.
+    ///
+    /// The zero offset and range policy are kept; re-tare if the new range differs.
+    pub fn set_config(&mut self, config: MprConfig) {
+        self.config = config;
+    }
+
+    pub fn zero_offset(&self) -> ZeroOffset {
+        self.zero_offset
+    }
+
+    /// Replaces the zero offset; `ZeroOffset::default()` clears it.
+    pub fn set_zero_offset(&mut self, zero_offset: ZeroOffset) {
+        self.zero_offset = zero_offset;
+    }
+
+    pub fn range_policy(&self) -> RangePolicy {
+        self.range_policy
+    }
+
+    /// Sets how readings outside the transfer function limits are handled by every `read*`
+    /// method except `read_raw*`. Defaults to `RangePolicy::Extrapolate`.
+    pub fn set_range_policy(&mut self, range_policy: RangePolicy) {
+        self.range_policy = range_policy;
     }
 
     /// Reads the sensor status byte.
-    #[maybe_async::maybe_async]
+    #[bisync]
     pub async fn status(&mut self) -> Result<Status, I::Error> {
         let mut buf = [0u8; 1];
         self.interface.read_reg(&mut buf).await?;
         Ok(Status::from_bits(buf[0]))
     }
-}
\ No newline at end of file
+
+    pub(super) fn address(&self) -> Option<u8> {
+        self.interface.address()
+    }
+
+    pub(super) fn reading(&self, raw_data: u32) -> Result<Reading, I::Error> {
+        self.apply_range_policy(self.config.reading(raw_data, self.zero_offset))
+    }
+
+    fn averaged(&self, samples: Samples<I::Error>) -> Result<AveragedReading, I::Error> {
+        let mut averaged = samples.finish(|raw_data| self.config.reading(raw_data, self.zero_offset))?;
+        averaged.reading = self.apply_range_policy(averaged.reading)?;
+        Ok(averaged)
+    }
+
+    fn apply_range_policy(&self, reading: Reading) -> Result<Reading, I::Error> {
+        match (self.range_policy, reading.range_status()) {
+            (RangePolicy::Extrapolate, _) | (_, RangeStatus::InRange) => Ok(reading),
+            (RangePolicy::Clamp, _) => Ok(reading.clamped()),
+            (RangePolicy::Error, _) => Err(Fault::OutOfRange.into())
+        }
+    }
+
+    /// Reads the status byte and raw data, separating bus errors (outer) from status errors
+    /// (inner).
+    #[bisync]
+    async fn read_frame(&mut self) -> Result<Result<u32, I::Error>, I::Error> {
+        let mut buf = [0u8; 4];
+        self.interface.read_reg(&mut buf).await?;
+
+        let status = Status::from_bits(buf[0]);
+        Ok(self.interface.validate_status(status)
+            .map(|_| ((buf[1] as u32) << 16) + ((buf[2] as u32) << 8) + buf[3] as u32))
+    }
+
+    #[bisync]
+    async fn samples_with_delay<D: DelayNs>(&mut self, n: u16, mut delay: D) -> Result<Samples<I::Error>, I::Error> {
+        let mut samples = Samples::new(self.read_frame_with_delay(&mut delay).await?);
+        for _ in 1..n {
+            samples.push(self.read_frame_with_delay(&mut delay).await?);
+        }
+        Ok(samples)
+    }
+
+    #[bisync]
+    async fn read_frame_with_delay<D: DelayNs>(&mut self, mut delay: D) -> Result<Result<u32, I::Error>, I::Error> {
+        self.exit_standby().await?;
+        delay.delay_ms(EXIT_STANDBY_DELAY_MS).await;
+        self.read_frame().await
+    }
+
+    #[bisync]
+    async fn read_frame_with_polling<D: DelayNs>(
+        &mut self,
+        delay: D,
+        poll_interval_us: u32,
+        timeout_ms: u32
+    ) -> Result<Result<u32, I::Error>, I::Error> {
+        self.exit_standby().await?;
+        self.wait_while_busy(delay, poll_interval_us, timeout_ms).await?;
+        self.read_frame().await
+    }
+
+    #[bisync]
+    async fn read_frame_with_eoc<E: Eoc, D: DelayNs>(
+        &mut self,
+        eoc: &mut E,
+        delay: D,
+        timeout_ms: Option<u32>
+    ) -> Result<Result<u32, I::Error>, I::Error> {
+        self.exit_standby().await?;
+        wait_for_eoc(eoc, delay, timeout_ms).await?;
+        self.read_frame().await
+    }
+
+    #[bisync]
+    pub(super) async fn wait_while_busy<D: DelayNs>(
+        &mut self,
+        mut delay: D,
+        poll_interval_us: u32,
+        timeout_ms: u32
+    ) -> Result<(), I::Error> {
+        // a zero interval would never advance the timeout
+        let poll_interval_us = poll_interval_us.max(1);
+        let timeout_us = timeout_ms.saturating_mul(1_000);
+        let mut elapsed_us: u32 = 0;
+        loop {
+            delay.delay_us(poll_interval_us).await;
+            elapsed_us = elapsed_us.saturating_add(poll_interval_us);
+            if !self.status().await?.is_busy() {
+                return Ok(())
+            }
+            if elapsed_us >= timeout_us {
+                return Err(Fault::Timeout.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'a> Mpr<I2cInterface<SimI2c<'a>>> {
+    /// Sensor on `sim` at the default address, configured with `test_config`.
+    pub(crate) fn sim_i2c(sim: &'a SimMpr<'a>) -> Self {
+        Mpr::with_interface(I2cInterface::new(sim.i2c(), 0x18), test_config())
+    }
+}
+
+#[cfg(test)]
+impl<'a> Mpr<SpiInterface<SimSpi<'a>>> {
+    /// Sensor on `sim`, configured with `test_config`.
+    pub(crate) fn sim_spi(sim: &'a SimMpr<'a>) -> Self {
+        Mpr::with_interface(SpiInterface::new(sim.spi()), test_config())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+    use std::vec;
+    use approx::relative_eq;
+    use embedded_hal::i2c::{ErrorKind as I2cErrorKind, NoAcknowledgeSource};
+    use embedded_hal::spi::ErrorKind as SpiErrorKind;
+    use crate::{Calibration, MprSpiError, PressureUnit, TransferFunction};
+    use crate::sim::{test_config, SimFaults, SimMpr};
+    use embedded_hal_mock::eh1::MockError;
+    use embedded_hal_mock::eh1::digital::Mock as PinMock;
+    use std::io::ErrorKind;
+    use super::super::{block_on, eoc_high};
+    use super::*;
+
+    fn spi_transaction(transaction: SpiTransaction<u8>) -> [SpiTransaction<u8>; 3] {
+        [SpiTransaction::transaction_start(), transaction, SpiTransaction::transaction_end()]
+    }
+
+    #[test]
+    fn i2c_exit_standby_ok() {
+        let expectations = [I2cTransaction::write(0x18, vec![0xaa, 0x00, 0x00])];
+        let mut i2c = I2cMock::new(&expectations);
+        let mut sensor = Mpr::new_i2c(i2c.clone(), 0x18, test_config()).unwrap();
+        assert_eq!(block_on(sensor.exit_standby()), Ok(None));
+        i2c.done();
+    }
+
+    #[test]
+    fn spi_exit_standby_returns_status() {
+        let expectations = spi_transaction(SpiTransaction::transfer(vec![0xaa, 0x00, 0x00], vec![0x60, 0x00, 0x00]));
+        let mut spi = SpiMock::new(&expectations);
+        let mut sensor = Mpr::new_spi(spi.clone(), test_config()).unwrap();
+        let status = block_on(sensor.exit_standby()).unwrap().unwrap();
+        assert!(status.is_powered());
+        assert!(status.is_busy());
+        spi.done();
+    }
+
+    #[test]
+    fn spi_read_raw_ok() {
+        let expectations = spi_transaction(
+            SpiTransaction::transfer_in_place(vec![0xf0, 0x00, 0x00, 0x00], vec![0x40, 0x80, 0x01, 0x02])
+        );
+        let mut spi = SpiMock::new(&expectations);
+        let mut sensor = Mpr::new_spi(spi.clone(), test_config()).unwrap();
+        assert_eq!(block_on(sensor.read_raw()), Ok(0x80_01_02));
+        spi.done();
+    }
+
+    #[test]
+    fn spi_read_raw_busy() {
+        let expectations = spi_transaction(
+            SpiTransaction::transfer_in_place(vec![0xf0, 0x00, 0x00, 0x00], vec![0x60, 0x80, 0x01, 0x02])
+        );
+        let mut spi = SpiMock::new(&expectations);
+        let mut sensor = Mpr::new_spi(spi.clone(), test_config()).unwrap();
+        assert_eq!(block_on(sensor.read_raw()), Err(MprSpiError::Busy));
+        spi.done();
+    }
+
+    #[test]
+    fn spi_read_raw_not_powered() {
+        let expectations = spi_transaction(
+            SpiTransaction::transfer_in_place(vec![0xf0, 0x00, 0x00, 0x00], vec![0x00, 0x00, 0x00, 0x00])
+        );
+        let mut spi = SpiMock::new(&expectations);
+        let mut sensor = Mpr::new_spi(spi.clone(), test_config()).unwrap();
+        assert_eq!(block_on(sensor.read_raw()), Err(MprSpiError::NotPowered));
+        spi.done();
+    }
+
+    #[test]
+    fn spi_status_ok() {
+        let expectations = spi_transaction(SpiTransaction::transfer_in_place(vec![0xf0], vec![0x44]));
+        let mut spi = SpiMock::new(&expectations);
+        let mut sensor = Mpr::new_spi(spi.clone(), test_config()).unwrap();
+        let status = block_on(sensor.status()).unwrap();
+        assert!(status.is_powered());
+        assert!(!status.integrity_test_passed());
+        spi.done();
+    }
+
+    #[test]
+    fn sim_i2c_read_with_delay_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(12.5);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert!(relative_eq!(reading.psi(), 12.5, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), EXIT_STANDBY_DELAY_MS as u64 * 1_000);
+    }
+
+    #[test]
+    fn sim_spi_read_with_delay_ok() {
+        let config = MprConfig::new(0.0, 60.0, PressureUnit::Mbar, TransferFunction::C);
+        let sim = SimMpr::new(config);
+        sim.set_pressure(42.0);
+        let mut sensor = Mpr::new_spi(sim.spi(), config).unwrap();
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert!(relative_eq!(reading.mbar(), 42.0, epsilon = 1e-3));
+    }
+
+    #[test]
+    fn sim_read_before_conversion_busy() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_spi(&sim);
+        block_on(sensor.exit_standby()).unwrap();
+        assert_eq!(block_on(sensor.read_raw()), Err(MprSpiError::Busy));
+    }
+
+    #[test]
+    fn sim_read_holds_previous_conversion() {
+        let sim = SimMpr::new(test_config());
+        sim.set_raw_data(0x12_34_56);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Ok(0x12_34_56));
+        sim.set_raw_data(0x65_43_21);
+        assert_eq!(block_on(sensor.read_raw()), Ok(0x12_34_56));
+    }
+
+    #[test]
+    fn sim_read_with_polling_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_conversion_time_us(4_200);
+        sim.set_pressure(20.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let reading = block_on(sensor.read_with_polling(sim.delay(), 500, 50)).unwrap();
+        assert!(relative_eq!(reading.psi(), 20.0, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), 4_500);
+    }
+
+    #[test]
+    fn sim_read_with_polling_timeout() {
+        let sim = SimMpr::new(test_config());
+        sim.set_conversion_time_us(20_000);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_polling(sim.delay(), 1_000, 5)), Err(MprI2cError::Timeout));
+    }
+
+    #[test]
+    fn sim_read_with_polling_zero_interval_timeout() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { stuck_busy: true, ..Default::default() });
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_polling(sim.delay(), 0, 5)), Err(MprI2cError::Timeout));
+        assert_eq!(sim.now_us(), 5_000);
+    }
+
+    #[test]
+    fn i2c_release_ok() {
+        let sensor = Mpr::new_i2c(I2cMock::new(&[]), 0x18, test_config()).unwrap();
+        let (mut i2c, released_config) = sensor.release();
+        assert_eq!(released_config, test_config());
+        i2c.done();
+    }
+
+    #[test]
+    fn spi_release_ok() {
+        let mut spi = SpiMock::new(&[]);
+        let sensor = Mpr::new_spi(spi.clone(), test_config()).unwrap();
+        let (_, released_config) = sensor.release();
+        assert_eq!(released_config, test_config());
+        spi.done();
+    }
+
+    #[test]
+    fn sim_set_config_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_raw_data(0x80_00_00);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let config = MprConfig::new(0.0, 60.0, PressureUnit::Mbar, TransferFunction::A);
+        sensor.set_config(config);
+        assert_eq!(sensor.config(), config);
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert_eq!(reading.unit, PressureUnit::Mbar);
+        assert!(relative_eq!(reading.mbar(), 30.0, epsilon = 1e-3));
+    }
+
+    #[test]
+    fn sim_read_with_eoc_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(7.5);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let reading = block_on(sensor.read_with_eoc(&mut sim.eoc_pin(), sim.delay(), Some(10))).unwrap();
+        assert!(relative_eq!(reading.psi(), 7.5, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), 5_000);
+        sim.set_raw_data(0x12_34_56);
+        assert_eq!(block_on(sensor.read_raw_with_eoc(&mut sim.eoc_pin(), sim.delay(), None)), Ok(0x12_34_56));
+    }
+
+    #[test]
+    fn sim_read_with_eoc_timeout() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { stuck_busy: true, ..Default::default() });
+        let mut sensor = Mpr::sim_spi(&sim);
+        assert_eq!(
+            block_on(sensor.read_raw_with_eoc(&mut sim.eoc_pin(), sim.delay(), Some(5))),
+            Err(MprSpiError::Timeout)
+        );
+        assert_eq!(sim.now_us(), 5_000);
+    }
+
+    #[test]
+    fn sim_read_with_eoc_pin_error() {
+        let sim = SimMpr::new(test_config());
+        let mut eoc = PinMock::new(&[eoc_high().with_error(MockError::Io(ErrorKind::Other))]);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_with_eoc(&mut eoc, sim.delay(), Some(10))).err(), Some(MprI2cError::Pin));
+        eoc.done();
+    }
+
+    #[test]
+    fn sim_read_averaged_with_eoc_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(12.5);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let averaged = block_on(sensor.read_averaged_with_eoc(3, &mut sim.eoc_pin(), sim.delay(), Some(10))).unwrap();
+        assert_eq!(averaged.samples, 3);
+        assert!(relative_eq!(averaged.reading.psi(), 12.5, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), 15_000);
+    }
+
+    #[test]
+    fn sim_status_ok() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let status = block_on(sensor.status()).unwrap();
+        assert!(status.is_powered());
+        assert!(!status.is_busy());
+        block_on(sensor.exit_standby()).unwrap();
+        assert!(block_on(sensor.status()).unwrap().is_busy());
+    }
+
+    #[test]
+    fn sim_i2c_address_nak() {
+        let sim = SimMpr::new(test_config());
+        sim.set_address(0x28);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(
+            block_on(sensor.status()),
+            Err(MprI2cError::I2c(I2cErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)))
+        );
+    }
+
+    #[test]
+    fn sim_fault_nak() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { nak: true, ..Default::default() });
+        let mut i2c_sensor = Mpr::sim_i2c(&sim);
+        let mut spi_sensor = Mpr::sim_spi(&sim);
+        assert_eq!(
+            block_on(i2c_sensor.read_raw_with_delay(sim.delay())),
+            Err(MprI2cError::I2c(I2cErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)))
+        );
+        assert_eq!(block_on(spi_sensor.read_raw_with_delay(sim.delay())), Err(MprSpiError::Spi(SpiErrorKind::Other)));
+    }
+
+    #[test]
+    fn sim_fault_math_saturation() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { math_saturation: true, ..Default::default() });
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Err(MprI2cError::MathSaturation));
+    }
+
+    #[test]
+    fn sim_fault_integrity_error() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { integrity_error: true, ..Default::default() });
+        let mut sensor = Mpr::sim_spi(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Err(MprSpiError::IntegrityTest));
+    }
+
+    #[test]
+    fn sim_fault_not_powered() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { not_powered: true, ..Default::default() });
+        let mut i2c_sensor = Mpr::sim_i2c(&sim);
+        let mut spi_sensor = Mpr::sim_spi(&sim);
+        assert_eq!(block_on(i2c_sensor.read_raw_with_delay(sim.delay())), Err(MprI2cError::NotPowered));
+        assert_eq!(block_on(spi_sensor.exit_standby()), Ok(Some(Status::from_bits(0x00))));
+        assert_eq!(block_on(spi_sensor.read_raw()), Err(MprSpiError::NotPowered));
+    }
+
+    #[test]
+    fn sim_fault_stuck_busy() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { stuck_busy: true, ..Default::default() });
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Err(MprI2cError::Busy));
+        assert_eq!(block_on(sensor.read_raw_with_polling(sim.delay(), 1_000, 20)), Err(MprI2cError::Timeout));
+    }
+
+    #[test]
+    fn sim_fault_truncated_read() {
+        let sim = SimMpr::new(test_config());
+        sim.set_raw_data(0x12_34_56);
+        sim.set_faults(SimFaults { truncated_read: Some(2), ..Default::default() });
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Ok(0x12_ff_ff));
+        sim.set_faults(SimFaults { truncated_read: Some(0), ..Default::default() });
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Err(MprI2cError::Busy));
+    }
+
+    #[test]
+    fn sim_fault_frozen_output() {
+        let sim = SimMpr::new(test_config());
+        sim.set_raw_data(0x12_34_56);
+        let mut sensor = Mpr::sim_spi(&sim);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Ok(0x12_34_56));
+        sim.set_faults(SimFaults { frozen_output: true, ..Default::default() });
+        sim.set_raw_data(0x65_43_21);
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Ok(0x12_34_56));
+        sim.set_faults(SimFaults::default());
+        assert_eq!(block_on(sensor.read_raw_with_delay(sim.delay())), Ok(0x65_43_21));
+    }
+
+    #[test]
+    fn sim_read_averaged_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let averaged = block_on(sensor.read_averaged(8, sim.delay())).unwrap();
+        assert_eq!(averaged.samples, 8);
+        assert_eq!(averaged.min, averaged.max);
+        assert_eq!(averaged.std_dev, 0.0);
+        assert!(relative_eq!(averaged.reading.psi(), 5.0, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), 8 * EXIT_STANDBY_DELAY_MS as u64 * 1_000);
+    }
+
+    #[test]
+    fn sim_read_averaged_with_polling_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_spi(&sim);
+        let averaged = block_on(sensor.read_averaged_with_polling(4, sim.delay(), 1_000, 50)).unwrap();
+        assert_eq!(averaged.samples, 4);
+        assert_eq!(sim.now_us(), 4 * 5_000);
+    }
+
+    #[test]
+    fn sim_read_averaged_no_valid_samples() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { math_saturation: true, ..Default::default() });
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(block_on(sensor.read_averaged(4, sim.delay())).err(), Some(MprI2cError::MathSaturation));
+    }
+
+    #[test]
+    fn sim_tare_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(0.25);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let zero_offset = block_on(sensor.tare(4, sim.delay())).unwrap();
+        assert_eq!(zero_offset, sensor.zero_offset());
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 0.0, epsilon = 1e-4));
+        sim.set_pressure(10.25);
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 10.0, epsilon = 1e-4));
+        let averaged = block_on(sensor.read_averaged(2, sim.delay())).unwrap();
+        assert!(relative_eq!(averaged.reading.psi(), 10.0, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn sim_tare_under_range_with_error_policy() {
+        let sim = SimMpr::new(test_config());
+        sim.set_raw_data(1_677_700);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        sensor.set_range_policy(RangePolicy::Error);
+        assert_eq!(block_on(sensor.read_with_delay(sim.delay())).err(), Some(MprI2cError::OutOfRange));
+        let zero_offset = block_on(sensor.tare(4, sim.delay())).unwrap();
+        assert_eq!(zero_offset.counts(), 1_677_700 - 1_677_722);
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 0.0, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn sim_zero_offset_restored() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(0.25);
+        let mut sensor = Mpr::sim_spi(&sim);
+        let bytes = block_on(sensor.tare(1, sim.delay())).unwrap().to_bytes();
+        let mut sensor = Mpr::sim_spi(&sim);
+        sensor.set_zero_offset(ZeroOffset::from_bytes(bytes));
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 0.0, epsilon = 1e-4));
+        sensor.set_zero_offset(ZeroOffset::default());
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 0.25, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn sim_read_calibrated() {
+        let calibration = Calibration::two_point((1_700_000, 0.0), (15_000_000, 25.0), &test_config()).unwrap();
+        let config = test_config().with_calibration(calibration);
+        let sim = SimMpr::new(config);
+        sim.set_raw_data(8_350_000);
+        let mut sensor = Mpr::new_i2c(sim.i2c(), 0x18, config).unwrap();
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert_eq!(reading.raw_data, 8_350_000);
+        assert!(relative_eq!(reading.psi(), 12.5, epsilon = 1e-4));
+        sim.set_raw_data(1_700_250);
+        block_on(sensor.tare(2, sim.delay())).unwrap();
+        sim.set_raw_data(15_000_250);
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 25.0, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn sim_range_policy() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(26.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        assert_eq!(sensor.range_policy(), RangePolicy::Extrapolate);
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert_eq!(reading.range_status(), RangeStatus::OverRange);
+        assert!(relative_eq!(reading.psi(), 26.0, epsilon = 1e-4));
+        sensor.set_range_policy(RangePolicy::Clamp);
+        assert!(relative_eq!(block_on(sensor.read_with_delay(sim.delay())).unwrap().psi(), 25.0, epsilon = 1e-4));
+        sensor.set_range_policy(RangePolicy::Error);
+        assert_eq!(block_on(sensor.read_with_delay(sim.delay())).err(), Some(MprI2cError::OutOfRange));
+        assert_eq!(block_on(sensor.read_averaged(2, sim.delay())).err(), Some(MprI2cError::OutOfRange));
+        assert!(block_on(sensor.read_raw_with_delay(sim.delay())).is_ok());
+        sim.set_pressure(12.5);
+        assert!(block_on(sensor.read_with_delay(sim.delay())).is_ok());
+    }
+}
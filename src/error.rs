@@ -0,0 +1,78 @@
+use self::private::Fault;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MprI2cError<E> {
+    Busy,
+    I2c(E),
+    InvalidAddress,
+    IntegrityTest,
+    MathSaturation,
+    NotPowered,
+    /// `poll` or `poll_eoc` without a pending `trigger`; only raised by the blocking driver.
+    NotTriggered,
+    OutOfRange,
+    Overrun,
+    Pin,
+    Timeout,
+}
+impl<E> From<Fault> for MprI2cError<E> {
+    fn from(fault: Fault) -> Self {
+        match fault {
+            Fault::IntegrityTest => MprI2cError::IntegrityTest,
+            Fault::NotPowered => MprI2cError::NotPowered,
+            Fault::NotTriggered => MprI2cError::NotTriggered,
+            Fault::OutOfRange => MprI2cError::OutOfRange,
+            Fault::Overrun => MprI2cError::Overrun,
+            Fault::Pin => MprI2cError::Pin,
+            Fault::Timeout => MprI2cError::Timeout,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MprSpiError<E> {
+    Busy,
+    IntegrityTest,
+    MathSaturation,
+    NotPowered,
+    /// `poll` or `poll_eoc` without a pending `trigger`; only raised by the blocking driver.
+    NotTriggered,
+    OutOfRange,
+    Overrun,
+    Pin,
+    Spi(E),
+    Timeout,
+}
+impl<E> From<Fault> for MprSpiError<E> {
+    fn from(fault: Fault) -> Self {
+        match fault {
+            Fault::IntegrityTest => MprSpiError::IntegrityTest,
+            Fault::NotPowered => MprSpiError::NotPowered,
+            Fault::NotTriggered => MprSpiError::NotTriggered,
+            Fault::OutOfRange => MprSpiError::OutOfRange,
+            Fault::Overrun => MprSpiError::Overrun,
+            Fault::Pin => MprSpiError::Pin,
+            Fault::Timeout => MprSpiError::Timeout,
+        }
+    }
+}
+
+/// Error building an `MprArray`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MprArrayError {
+    /// Two I2C sensors use this address.
+    DuplicateAddress(u8),
+}
+
+pub(crate) mod private {
+    /// Interface-independent failures raised by the driver and mapped into each interface error.
+    pub enum Fault {
+        IntegrityTest,
+        NotPowered,
+        NotTriggered,
+        OutOfRange,
+        Overrun,
+        Pin,
+        Timeout,
+    }
+}
@@ -0,0 +1,123 @@
+//! Flow from differential pressure across an orifice plate, venturi or pitot tube.
+//!
+//! Orifice and venturi flow follows `Q = Cd * A * sqrt(2 * dp / (density * (1 - r^2)))` with
+//! `A` the throat (bore) area and `r` the throat to pipe area ratio. Pitot flow is the duct area
+//! times `v = C * sqrt(2 * dp / density)`. A negative differential gives a negative flow, so
+//! bidirectional flow can be measured with compound parts or two sensors read as a
+//! `Differential` (in `asynch` and `blocking`).
+
+use crate::Reading;
+
+/// Dry air at 20°C and 101.325 kPa.
+pub const AIR_DENSITY_KG_M3: f32 = 1.204;
+/// Fresh water at 20°C.
+pub const WATER_DENSITY_KG_M3: f32 = 998.2;
+
+/// Primary element that creates the differential pressure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlowElement {
+    Orifice { discharge_coefficient: f32, throat_area_m2: f32, area_ratio: f32 },
+    Venturi { discharge_coefficient: f32, throat_area_m2: f32, area_ratio: f32 },
+    Pitot { coefficient: f32, duct_area_m2: f32 },
+}
+impl FlowElement {
+    /// Sharp-edged orifice plate with a typical discharge coefficient of 0.61.
+    pub fn orifice(throat_area_m2: f32, area_ratio: f32) -> Self {
+        FlowElement::Orifice { discharge_coefficient: 0.61, throat_area_m2, area_ratio }
+    }
+
+    /// Machined venturi with a typical discharge coefficient of 0.98.
+    pub fn venturi(throat_area_m2: f32, area_ratio: f32) -> Self {
+        FlowElement::Venturi { discharge_coefficient: 0.98, throat_area_m2, area_ratio }
+    }
+
+    /// Pitot-static tube with a coefficient of 1.0, assuming the measured velocity is the mean
+    /// duct velocity.
+    pub fn pitot(duct_area_m2: f32) -> Self {
+        FlowElement::Pitot { coefficient: 1.0, duct_area_m2 }
+    }
+}
+
+/// Converts differential pressure to flow for an element and fluid density.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlowMeter {
+    pub element: FlowElement,
+    pub density_kg_m3: f32
+}
+impl FlowMeter {
+    pub fn new(element: FlowElement, density_kg_m3: f32) -> Self {
+        Self { element, density_kg_m3 }
+    }
+
+    /// Volumetric flow in m³/s for a differential pressure in pascals.
+    pub fn volumetric_flow_m3_s(&self, differential_pa: f32) -> f32 {
+        let velocity_head = signed_sqrt(2.0 * differential_pa / self.density_kg_m3);
+        match self.element {
+            FlowElement::Orifice { discharge_coefficient, throat_area_m2, area_ratio } |
+            FlowElement::Venturi { discharge_coefficient, throat_area_m2, area_ratio } => {
+                let approach_factor = libm::sqrtf(1.0 - area_ratio * area_ratio);
+                discharge_coefficient * throat_area_m2 * velocity_head / approach_factor
+            },
+            FlowElement::Pitot { coefficient, duct_area_m2 } => coefficient * duct_area_m2 * velocity_head,
+        }
+    }
+
+    /// Mass flow in kg/s for a differential pressure in pascals.
+    pub fn mass_flow_kg_s(&self, differential_pa: f32) -> f32 {
+        self.volumetric_flow_m3_s(differential_pa) * self.density_kg_m3
+    }
+
+    /// Volumetric flow in m³/s for a differential or gauge reading.
+    pub fn volumetric_flow_from_reading(&self, reading: &Reading) -> f32 {
+        self.volumetric_flow_m3_s(reading.kpa() * 1_000.0)
+    }
+
+    /// Mass flow in kg/s for a differential or gauge reading.
+    pub fn mass_flow_from_reading(&self, reading: &Reading) -> f32 {
+        self.mass_flow_kg_s(reading.kpa() * 1_000.0)
+    }
+}
+
+fn signed_sqrt(value: f32) -> f32 {
+    if value < 0.0 { -libm::sqrtf(-value) } else { libm::sqrtf(value) }
+}
+
+/// Error of the high or low sensor of a `Differential`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DifferentialError<H, L> {
+    High(H),
+    Low(L)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use crate::{PressureUnit, TransferFunction};
+    use super::*;
+
+    #[test]
+    fn orifice_flow_ok() {
+        let meter = FlowMeter::new(FlowElement::orifice(1e-3, 0.25), WATER_DENSITY_KG_M3);
+        // 0.61 * 1e-3 * sqrt(2 * 10_000 / 998.2) / sqrt(1 - 0.0625)
+        assert!(relative_eq!(meter.volumetric_flow_m3_s(10_000.0), 2.8200e-3, epsilon = 1e-6));
+        assert!(relative_eq!(meter.mass_flow_kg_s(10_000.0), 2.8149, epsilon = 1e-3));
+        assert!(relative_eq!(meter.volumetric_flow_m3_s(-10_000.0), -2.8200e-3, epsilon = 1e-6));
+        assert_eq!(meter.volumetric_flow_m3_s(0.0), 0.0);
+    }
+
+    #[test]
+    fn venturi_flow_ok() {
+        let element = FlowElement::Venturi { discharge_coefficient: 1.0, throat_area_m2: 1e-3, area_ratio: 0.0 };
+        let meter = FlowMeter::new(element, 2.0);
+        assert!(relative_eq!(meter.volumetric_flow_m3_s(100.0), 1e-2, epsilon = 1e-7));
+    }
+
+    #[test]
+    fn pitot_flow_ok() {
+        let meter = FlowMeter::new(FlowElement::pitot(0.05), AIR_DENSITY_KG_M3);
+        // v = sqrt(2 * 60.2 / 1.204) = 10 m/s
+        assert!(relative_eq!(meter.volumetric_flow_m3_s(60.2), 0.5, epsilon = 1e-5));
+        let reading = Reading::new(0.0, 60.0, PressureUnit::Mbar, 1_812_386, TransferFunction::A);
+        assert!(relative_eq!(meter.volumetric_flow_from_reading(&reading), 0.5, epsilon = 1e-3));
+    }
+}
@@ -1,40 +1,27 @@
-#[cfg(not(feature = "sync"))]
-use embedded_hal_async::i2c::I2c;
-#[cfg(not(feature = "sync"))]
-use embedded_hal_async::spi::SpiDevice;
-#[cfg(feature = "sync")]
-use embedded_hal::i2c::I2c;
-#[cfg(feature = "sync")]
-use embedded_hal::spi::SpiDevice;
+use crate::error::{MprI2cError, MprSpiError};
+use crate::error::private::Fault;
 use crate::registers::Status;
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum MprI2cError<E> {
-    I2c(E),
-    InvalidAddress,
-    IntegrityTest,
-    MathSaturation,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum MprSpiError<E> {
-    IntegrityTest,
-    MathSaturation,
-    Spi(E),
-}
+use super::{bisync, I2c, SpiDevice};
 
 mod private {
     pub trait Sealed {}
 }
 
-#[maybe_async::maybe_async(AFIT)]
+#[bisync(AFIT)]
 pub trait Interface: private::Sealed {
-    type Error;
+    type Error: From<Fault>;
 
     async fn read_reg(&mut self, buf: &mut [u8]) -> Result<(), Self::Error>;
 
-    async fn write_reg(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+    /// Writes a command, returning the status byte when the interface clocks one out (SPI).
+    async fn write_reg(&mut self, buf: &[u8; 3]) -> Result<Option<Status>, Self::Error>;
     fn validate_status(&self, status: Status) -> Result<(), Self::Error>;
+
+    /// I2C address, if the interface is addressed.
+    fn address(&self) -> Option<u8>;
+
+    /// Whether `error` is reported by a sensor that needs to be power cycled.
+    fn is_power_fault(error: &Self::Error) -> bool;
 }
 
 // I2C ---------------------------------------------------------------------------------------------
@@ -48,22 +35,33 @@ impl<I2C: I2c> I2cInterface<I2C> {
     pub(crate) fn new(device: I2C, address: u8) -> Self {
         Self { device, address }
     }
+
+    pub(crate) fn release(self) -> I2C {
+        self.device
+    }
 }
 impl<I2C: I2c>private::Sealed for I2cInterface<I2C> {}
 impl<I2C: I2c>Interface for I2cInterface<I2C> {
     type Error = MprI2cError<I2C::Error>;
 
-    #[maybe_async::maybe_async]
+    #[bisync]
     async fn read_reg(&mut self, buf: &mut [u8]) -> Result<(), MprI2cError<I2C::Error>> {
         self.device.read(self.address, buf).await.map_err(MprI2cError::I2c)
     }
 
-    #[maybe_async::maybe_async]
-    async fn write_reg(&mut self, buf: &[u8]) -> Result<(), MprI2cError<I2C::Error>> {
-        self.device.write(self.address, buf).await.map_err(MprI2cError::I2c)
+    #[bisync]
+    async fn write_reg(&mut self, buf: &[u8; 3]) -> Result<Option<Status>, MprI2cError<I2C::Error>> {
+        self.device.write(self.address, buf).await.map_err(MprI2cError::I2c)?;
+        Ok(None)
     }
 
     fn validate_status(&self, status: Status) -> Result<(), MprI2cError<I2C::Error>> {
+        if !status.is_powered() {
+            return Err(MprI2cError::NotPowered)
+        }
+        if status.is_busy() {
+            return Err(MprI2cError::Busy)
+        }
         if status.math_saturation_occurred() {
             return Err(MprI2cError::MathSaturation)
         }
@@ -72,10 +70,20 @@ impl<I2C: I2c>Interface for I2cInterface<I2C> {
         }
         Ok(())
     }
+
+    fn address(&self) -> Option<u8> {
+        Some(self.address)
+    }
+
+    fn is_power_fault(error: &MprI2cError<I2C::Error>) -> bool {
+        matches!(error, MprI2cError::IntegrityTest | MprI2cError::NotPowered)
+    }
 }
 
 // SPI ---------------------------------------------------------------------------------------------
 
+const SPI_NOP_CMD: u8 = 0xf0;
+
 pub struct SpiInterface<SPI> {
     device: SPI
 }
@@ -83,22 +91,38 @@ impl<SPI: SpiDevice> SpiInterface<SPI> {
     pub(crate) fn new(device: SPI) -> Self {
         Self { device }
     }
+
+    pub(crate) fn release(self) -> SPI {
+        self.device
+    }
 }
 impl<SPI: SpiDevice>private::Sealed for SpiInterface<SPI> {}
 impl<SPI: SpiDevice>Interface for SpiInterface<SPI> {
     type Error = MprSpiError<SPI::Error>;
 
-    #[maybe_async::maybe_async]
+    /// Clocks out `0xF0` followed by `0x00` padding while reading the status and data bytes.
+    #[bisync]
     async fn read_reg(&mut self, buf: &mut [u8]) -> Result<(), MprSpiError<SPI::Error>> {
-        self.device.read(buf).await.map_err(MprSpiError::Spi)
+        buf.fill(0x00);
+        buf[0] = SPI_NOP_CMD;
+        self.device.transfer_in_place(buf).await.map_err(MprSpiError::Spi)
     }
 
-    #[maybe_async::maybe_async]
-    async fn write_reg(&mut self, buf: &[u8]) -> Result<(), MprSpiError<SPI::Error>> {
-        self.device.write(buf).await.map_err(MprSpiError::Spi)
+    /// Full-duplex write; the sensor returns its status byte on MISO during the first byte.
+    #[bisync]
+    async fn write_reg(&mut self, buf: &[u8; 3]) -> Result<Option<Status>, MprSpiError<SPI::Error>> {
+        let mut miso = [0u8; 3];
+        self.device.transfer(&mut miso, buf).await.map_err(MprSpiError::Spi)?;
+        Ok(Some(Status::from_bits(miso[0])))
     }
 
     fn validate_status(&self, status: Status) -> Result<(), MprSpiError<SPI::Error>> {
+        if !status.is_powered() {
+            return Err(MprSpiError::NotPowered)
+        }
+        if status.is_busy() {
+            return Err(MprSpiError::Busy)
+        }
         if status.math_saturation_occurred() {
             return Err(MprSpiError::MathSaturation)
         }
@@ -107,4 +131,12 @@ impl<SPI: SpiDevice>Interface for SpiInterface<SPI> {
         }
         Ok(())
     }
+
+    fn address(&self) -> Option<u8> {
+        None
+    }
+
+    fn is_power_fault(error: &MprSpiError<SPI::Error>) -> bool {
+        matches!(error, MprSpiError::IntegrityTest | MprSpiError::NotPowered)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+//! Hydrostatic liquid level from gauge parts, e.g. on a bubbler tube or at the bottom of a tank.
+//!
+//! The column height above the sensing point is `h = p / (density * gravity)`.
+
+use crate::Reading;
+
+/// Standard acceleration of gravity.
+pub const STANDARD_GRAVITY_M_S2: f32 = 9.80665;
+
+/// Liquid properties used to convert gauge pressure to column height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fluid {
+    pub density_kg_m3: f32,
+    pub gravity_m_s2: f32
+}
+impl Fluid {
+    /// Fresh water at 4°C.
+    pub const WATER: Fluid = Fluid::new(1_000.0);
+    /// Fresh water at 20°C.
+    pub const WATER_20C: Fluid = Fluid::new(998.2);
+    /// Sea water at 20°C and 35 g/kg salinity.
+    pub const SEA_WATER: Fluid = Fluid::new(1_024.8);
+
+    /// Fluid of `density_kg_m3` under standard gravity.
+    pub const fn new(density_kg_m3: f32) -> Self {
+        Self { density_kg_m3, gravity_m_s2: STANDARD_GRAVITY_M_S2 }
+    }
+
+    /// Overrides the gravity, e.g. with the local value for the site.
+    pub const fn with_gravity(mut self, gravity_m_s2: f32) -> Self {
+        self.gravity_m_s2 = gravity_m_s2;
+        self
+    }
+
+    /// Column height in meters above the sensing point for a gauge reading.
+    pub fn height_m(&self, reading: &Reading) -> f32 {
+        self.height_m_from_pa(reading.kpa() * 1_000.0)
+    }
+
+    /// Column height in meters for a gauge pressure in pascals.
+    pub fn height_m_from_pa(&self, pressure_pa: f32) -> f32 {
+        pressure_pa / (self.density_kg_m3 * self.gravity_m_s2)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TankGeometryError {
+    TooFewPoints,
+    /// Heights must strictly increase and volumes must not decrease.
+    NotMonotonic
+}
+
+/// Level to volume lookup for tanks whose cross-section varies with height.
+///
+/// Points are `(height_m, volume)` pairs with heights measured from the same point as the
+/// column height (add the sensing point elevation to the table if it sits above the bottom).
+/// Volume is in whatever unit the table uses and is interpolated linearly between points and
+/// held at the first and last volumes outside the table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TankGeometry<'a> {
+    points: &'a [(f32, f32)]
+}
+impl<'a> TankGeometry<'a> {
+    pub fn new(points: &'a [(f32, f32)]) -> Result<Self, TankGeometryError> {
+        if points.len() < 2 {
+            return Err(TankGeometryError::TooFewPoints)
+        }
+        if !points.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1) {
+            return Err(TankGeometryError::NotMonotonic)
+        }
+        Ok(Self { points })
+    }
+
+    /// Volume at `height_m`.
+    pub fn volume(&self, height_m: f32) -> f32 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        if height_m <= first.0 {
+            return first.1
+        }
+        if height_m >= last.0 {
+            return last.1
+        }
+        let end = self.points.iter().position(|(height, _)| height_m < *height).unwrap_or(self.points.len() - 1);
+        let ((h0, v0), (h1, v1)) = (self.points[end - 1], self.points[end]);
+        v0 + (height_m - h0) / (h1 - h0) * (v1 - v0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use crate::{PressureUnit, TransferFunction};
+    use super::*;
+
+    #[test]
+    fn height_water_ok() {
+        assert!(relative_eq!(Fluid::WATER.height_m_from_pa(9_806.65), 1.0, epsilon = 1e-6));
+        assert!(relative_eq!(Fluid::SEA_WATER.height_m_from_pa(9_806.65), 0.97580, epsilon = 1e-4));
+        let fluid = Fluid::new(800.0).with_gravity(9.81);
+        assert!(relative_eq!(fluid.height_m_from_pa(7_848.0), 1.0, epsilon = 1e-6));
+    }
+
+    #[test]
+    fn height_reading_ok() {
+        // half of a 0 to 60 mbar gauge range is 3 kPa
+        let reading = Reading::new(0.0, 60.0, PressureUnit::Mbar, 8_388_608, TransferFunction::A);
+        assert!(relative_eq!(Fluid::WATER.height_m(&reading), 0.30591, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn tank_volume_ok() {
+        let points = [(0.0, 0.0), (0.5, 100.0), (1.0, 300.0)];
+        let tank = TankGeometry::new(&points).unwrap();
+        assert_eq!(tank.volume(-0.1), 0.0);
+        assert_eq!(tank.volume(0.25), 50.0);
+        assert_eq!(tank.volume(0.5), 100.0);
+        assert_eq!(tank.volume(0.75), 200.0);
+        assert_eq!(tank.volume(2.0), 300.0);
+    }
+
+    #[test]
+    fn tank_geometry_invalid() {
+        assert_eq!(TankGeometry::new(&[(0.0, 0.0)]), Err(TankGeometryError::TooFewPoints));
+        assert_eq!(TankGeometry::new(&[(0.0, 0.0), (0.0, 1.0)]), Err(TankGeometryError::NotMonotonic));
+        assert_eq!(TankGeometry::new(&[(0.0, 1.0), (1.0, 0.0)]), Err(TankGeometryError::NotMonotonic));
+    }
+}
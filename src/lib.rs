@@ -1,11 +1,33 @@
 #![no_std]
 #![allow(async_fn_in_trait)]
 
-pub use crate::driver::Mpr;
-pub use crate::interface::{MprI2cError, MprSpiError};
-pub use crate::typedefs::{MprConfig, Reading, TransferFunction};
+#[cfg(test)]
+extern crate std;
 
+pub use crate::averaging::AveragedReading;
+pub use crate::calibration::{Calibration, CalibrationError, MAX_CALIBRATION_POINTS, ZeroOffset};
+pub use crate::clock::Clock;
+pub use crate::error::{MprArrayError, MprI2cError, MprSpiError};
+pub use crate::probe::{Probe, VALID_I2C_ADDRESSES};
+pub use crate::part_number::{OutputType, PartNumber, PartNumberError, PressureReference};
+pub use crate::registers::Status;
+pub use crate::typedefs::{
+    CustomCounts, MprConfig, PressureUnit, RangePolicy, RangeStatus, Reading, TransferFunction, TransferFunctionError
+};
+pub use crate::asynch::{scan, Differential, Mpr, MprArray, PendingMeasurement, PoweredMpr, Stream};
+
+pub mod altitude;
+pub mod asynch;
+mod averaging;
+pub mod blocking;
+mod calibration;
+mod clock;
+mod error;
 mod registers;
 mod typedefs;
-mod interface;
-mod driver;
\ No newline at end of file
+pub mod level;
+pub mod flow;
+mod part_number;
+mod probe;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
\ No newline at end of file
@@ -0,0 +1,98 @@
+use crate::Reading;
+use super::{bisync, wait_for_eoc, DelayNs, Eoc};
+use super::driver::{Mpr, EXIT_STANDBY_DELAY_MS};
+use super::interface::Interface;
+
+/// Conversion started by `Mpr::start_measurement`, resolved into a Reading by waiting for it to
+/// finish.
+#[must_use = "the conversion is only read by resolving the measurement"]
+pub struct PendingMeasurement<'a, I> {
+    mpr: &'a mut Mpr<I>
+}
+impl<'a, I: Interface> PendingMeasurement<'a, I> {
+    pub(crate) fn new(mpr: &'a mut Mpr<I>) -> Self {
+        Self { mpr }
+    }
+
+    /// Waits a fixed delay covering the conversion time, then reads.
+    #[bisync]
+    pub async fn wait_delay<D: DelayNs>(self, mut delay: D) -> Result<Reading, I::Error> {
+        delay.delay_ms(EXIT_STANDBY_DELAY_MS).await;
+        self.mpr.read().await
+    }
+
+    /// Waits for the EOC pin to go high, then reads. Returns a timeout error if `timeout_ms`
+    /// elapses first.
+    #[bisync]
+    pub async fn wait_eoc<E: Eoc, D: DelayNs>(
+        self,
+        eoc: &mut E,
+        delay: D,
+        timeout_ms: Option<u32>
+    ) -> Result<Reading, I::Error> {
+        wait_for_eoc(eoc, delay, timeout_ms).await?;
+        self.mpr.read().await
+    }
+
+    /// Polls the busy flag every `poll_interval_us`, then reads. Returns a timeout error if the
+    /// sensor is still busy after `timeout_ms`.
+    #[bisync]
+    pub async fn poll<D: DelayNs>(self, delay: D, poll_interval_us: u32, timeout_ms: u32) -> Result<Reading, I::Error> {
+        self.mpr.wait_while_busy(delay, poll_interval_us, timeout_ms).await?;
+        self.mpr.read().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use embedded_hal_mock::eh1::digital::Mock as PinMock;
+    use std::vec;
+    use crate::MprI2cError;
+    use crate::sim::{test_config, SimMpr};
+    use super::super::{block_on, eoc_high};
+    use super::*;
+
+    #[test]
+    fn wait_delay_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let pending = block_on(sensor.start_measurement()).unwrap();
+        let reading = block_on(pending.wait_delay(sim.delay())).unwrap();
+        assert!(relative_eq!(reading.psi(), 5.0, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), EXIT_STANDBY_DELAY_MS as u64 * 1_000);
+    }
+
+    #[test]
+    fn wait_eoc_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        sim.set_conversion_time_us(0);
+        let mut sensor = Mpr::sim_spi(&sim);
+        let mut eoc = PinMock::new(&vec![eoc_high()]);
+        let pending = block_on(sensor.start_measurement()).unwrap();
+        let reading = block_on(pending.wait_eoc(&mut eoc, sim.delay(), Some(10))).unwrap();
+        assert!(relative_eq!(reading.psi(), 5.0, epsilon = 1e-4));
+        eoc.done();
+    }
+
+    #[test]
+    fn poll_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(5.0);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let pending = block_on(sensor.start_measurement()).unwrap();
+        let reading = block_on(pending.poll(sim.delay(), 1_000, 50)).unwrap();
+        assert!(relative_eq!(reading.psi(), 5.0, epsilon = 1e-4));
+        assert_eq!(sim.now_us(), 5_000);
+    }
+
+    #[test]
+    fn poll_timeout() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let pending = block_on(sensor.start_measurement()).unwrap();
+        assert_eq!(block_on(pending.poll(sim.delay(), 1_000, 2)).err(), Some(MprI2cError::Timeout));
+    }
+}
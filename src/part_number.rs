@@ -0,0 +1,211 @@
+use core::str::FromStr;
+use crate::{MprConfig, PressureUnit, TransferFunction};
+
+const PART_NUMBER_LEN: usize = 17;
+const SERIES: &str = "MPR";
+
+/// Gauge or absolute pressure reference. See datasheet Figure 4 Pressure Reference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PressureReference {
+    Absolute,
+    Gauge
+}
+
+/// Interface and, for I2C parts, the 7-bit device address. See datasheet Figure 4 Output Type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputType {
+    I2c(u8),
+    Spi
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PartNumberError {
+    InvalidLength,
+    InvalidSeries,
+    InvalidPressureRange,
+    InvalidPressureUnit,
+    InvalidPressureReference,
+    InvalidOptions,
+    InvalidOutputType,
+    InvalidTransferFunction,
+}
+
+/// Decoded Honeywell MPR catalog listing, e.g. `MPRLS0025PA00001A`.
+///
+/// Layout (datasheet Figure 4): `MPR` series, package, pressure port, 4-character pressure
+/// range, pressure unit, pressure reference, 2-digit options, 3-digit output type and transfer
+/// function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PartNumber {
+    pub config: MprConfig,
+    pub reference: PressureReference,
+    pub output: OutputType
+}
+impl PartNumber {
+    pub fn parse(part_number: &str) -> Result<Self, PartNumberError> {
+        if part_number.len() != PART_NUMBER_LEN || !part_number.is_ascii() {
+            return Err(PartNumberError::InvalidLength)
+        }
+        let bytes = part_number.as_bytes();
+        if !part_number.starts_with(SERIES) || !bytes[3..5].iter().all(u8::is_ascii_uppercase) {
+            return Err(PartNumberError::InvalidSeries)
+        }
+
+        let pressure_max = parse_milli(&bytes[5..9])
+            .filter(|max| *max > 0)
+            .ok_or(PartNumberError::InvalidPressureRange)?;
+        let unit = match bytes[9] {
+            b'B' => PressureUnit::Bar,
+            b'K' => PressureUnit::Kpa,
+            b'M' => PressureUnit::Mbar,
+            b'P' => PressureUnit::Psi,
+            b'Y' => PressureUnit::Mmhg,
+            _ => return Err(PartNumberError::InvalidPressureUnit)
+        };
+        let reference = match bytes[10] {
+            b'A' => PressureReference::Absolute,
+            b'G' => PressureReference::Gauge,
+            _ => return Err(PartNumberError::InvalidPressureReference)
+        };
+        if !bytes[11..13].iter().all(u8::is_ascii_digit) {
+            return Err(PartNumberError::InvalidOptions)
+        }
+        let output = match &part_number[13..16] {
+            "000" => OutputType::Spi,
+            "001" => OutputType::I2c(0x18),
+            "002" => OutputType::I2c(0x28),
+            "003" => OutputType::I2c(0x38),
+            "004" => OutputType::I2c(0x48),
+            "005" => OutputType::I2c(0x58),
+            "006" => OutputType::I2c(0x68),
+            "007" => OutputType::I2c(0x78),
+            _ => return Err(PartNumberError::InvalidOutputType)
+        };
+        let transfer_function = match bytes[16] {
+            b'A' => TransferFunction::A,
+            b'B' => TransferFunction::B,
+            b'C' => TransferFunction::C,
+            _ => return Err(PartNumberError::InvalidTransferFunction)
+        };
+
+        Ok(Self {
+            config: MprConfig::new_milli(0, pressure_max, unit, transfer_function),
+            reference,
+            output
+        })
+    }
+
+    /// I2C device address, or `None` for SPI parts.
+    pub fn i2c_address(&self) -> Option<u8> {
+        match self.output {
+            OutputType::I2c(address) => Some(address),
+            OutputType::Spi => None
+        }
+    }
+}
+
+/// Parses digits with an optional decimal point (e.g. `0025`, `01.6`) into thousandths.
+fn parse_milli(digits: &[u8]) -> Option<i32> {
+    let mut milli: i32 = 0;
+    // place value of the next fractional digit, once the decimal point has been seen
+    let mut place: Option<i32> = None;
+    for digit in digits {
+        match (digit, place) {
+            (b'.', None) => place = Some(100),
+            (b'0'..=b'9', None) => milli = milli * 10 + (digit - b'0') as i32 * 1_000,
+            (b'0'..=b'9', Some(value)) if value > 0 => {
+                milli += (digit - b'0') as i32 * value;
+                place = Some(value / 10);
+            },
+            _ => return None
+        }
+    }
+    Some(milli)
+}
+
+impl FromStr for PartNumber {
+    type Err = PartNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_psi_absolute_i2c_ok() {
+        let part = PartNumber::parse("MPRLS0025PA00001A").unwrap();
+        assert_eq!(part.config, MprConfig::new(0.0, 25.0, PressureUnit::Psi, TransferFunction::A));
+        assert_eq!(part.reference, PressureReference::Absolute);
+        assert_eq!(part.output, OutputType::I2c(0x18));
+        assert_eq!(part.i2c_address(), Some(0x18));
+    }
+
+    #[test]
+    fn parse_bar_gauge_ok() {
+        let part: PartNumber = "MPRLS0001BG00001C".parse().unwrap();
+        assert_eq!(part.config, MprConfig::new(0.0, 1.0, PressureUnit::Bar, TransferFunction::C));
+        assert_eq!(part.reference, PressureReference::Gauge);
+    }
+
+    #[test]
+    fn parse_fractional_range_ok() {
+        let part = PartNumber::parse("MPRLS01.6BA00007B").unwrap();
+        assert_eq!(part.config, MprConfig::new(0.0, 1.6, PressureUnit::Bar, TransferFunction::B));
+        assert_eq!(part.output, OutputType::I2c(0x78));
+    }
+
+    #[test]
+    fn parse_mbar_spi_ok() {
+        let part = PartNumber::parse("MPRLS0060MG00000A").unwrap();
+        assert_eq!(part.config, MprConfig::new(0.0, 60.0, PressureUnit::Mbar, TransferFunction::A));
+        assert_eq!(part.output, OutputType::Spi);
+        assert_eq!(part.i2c_address(), None);
+    }
+
+    #[test]
+    fn parse_invalid_length() {
+        assert_eq!(PartNumber::parse("MPRLS0025PA0001A"), Err(PartNumberError::InvalidLength));
+    }
+
+    #[test]
+    fn parse_invalid_series() {
+        assert_eq!(PartNumber::parse("ABPLS0025PA00001A"), Err(PartNumberError::InvalidSeries));
+    }
+
+    #[test]
+    fn parse_invalid_pressure_range() {
+        assert_eq!(PartNumber::parse("MPRLS0000PA00001A"), Err(PartNumberError::InvalidPressureRange));
+        assert_eq!(PartNumber::parse("MPRLS00X5PA00001A"), Err(PartNumberError::InvalidPressureRange));
+        assert_eq!(PartNumber::parse("MPRLS-025PA00001A"), Err(PartNumberError::InvalidPressureRange));
+        assert_eq!(PartNumber::parse("MPRLS0.2.PA00001A"), Err(PartNumberError::InvalidPressureRange));
+    }
+
+    #[test]
+    fn parse_invalid_pressure_unit() {
+        assert_eq!(PartNumber::parse("MPRLS0025XA00001A"), Err(PartNumberError::InvalidPressureUnit));
+    }
+
+    #[test]
+    fn parse_invalid_pressure_reference() {
+        assert_eq!(PartNumber::parse("MPRLS0025PD00001A"), Err(PartNumberError::InvalidPressureReference));
+    }
+
+    #[test]
+    fn parse_invalid_options() {
+        assert_eq!(PartNumber::parse("MPRLS0025PA0X001A"), Err(PartNumberError::InvalidOptions));
+    }
+
+    #[test]
+    fn parse_invalid_output_type() {
+        assert_eq!(PartNumber::parse("MPRLS0025PA00008A"), Err(PartNumberError::InvalidOutputType));
+    }
+
+    #[test]
+    fn parse_invalid_transfer_function() {
+        assert_eq!(PartNumber::parse("MPRLS0025PA00001D"), Err(PartNumberError::InvalidTransferFunction));
+    }
+}
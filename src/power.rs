@@ -0,0 +1,297 @@
+use embedded_hal::digital::OutputPin;
+use crate::Reading;
+use crate::error::private::Fault;
+use crate::registers::Status;
+use super::{bisync, DelayNs, Eoc};
+use super::driver::Mpr;
+use super::interface::Interface;
+
+/// Time allowed after switching the supply on before the first command.
+pub(crate) const POWER_UP_DELAY_MS: u32 = 5;
+/// Time the supply is held off during a power cycle.
+pub(crate) const POWER_OFF_DELAY_MS: u32 = 10;
+const DEFAULT_RETRIES: u8 = 1;
+
+/// Sensor whose supply is switched by a GPIO.
+///
+/// The memory integrity check only runs at power-up, so an `IntegrityTest` or `NotPowered` error
+/// from `read_with_delay`, `read_with_eoc` or `read_with_polling` is handled by power cycling the
+/// sensor and retrying; reads made through `sensor_mut` are not retried. The sensor can also be
+/// powered down between sparse measurements with `set_auto_power_down`.
+pub struct PoweredMpr<I, P> {
+    mpr: Mpr<I>,
+    power: P,
+    powered: bool,
+    auto_power_down: bool,
+    retries: u8
+}
+impl<I: Interface, P: OutputPin> PoweredMpr<I, P> {
+    /// The sensor is treated as off until `power_up`, or the first read, powers it up.
+    pub fn new(mpr: Mpr<I>, power: P) -> Self {
+        Self { mpr, power, powered: false, auto_power_down: false, retries: DEFAULT_RETRIES }
+    }
+
+    /// Returns the sensor and the power pin, leaving the supply as is.
+    pub fn release(self) -> (Mpr<I>, P) {
+        (self.mpr, self.power)
+    }
+
+    pub fn sensor_mut(&mut self) -> &mut Mpr<I> {
+        &mut self.mpr
+    }
+
+    /// Whether the last power-up confirmed the sensor is powered and passed its integrity test.
+    ///
+    /// False after a failed power-up even though the supply pin may still be driven high.
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Powers the sensor down after every read. Defaults to false.
+    pub fn set_auto_power_down(&mut self, auto_power_down: bool) {
+        self.auto_power_down = auto_power_down;
+    }
+
+    /// Sets how many power cycles a read may take to recover. Defaults to one.
+    pub fn set_retries(&mut self, retries: u8) {
+        self.retries = retries;
+    }
+
+    /// Switches the supply on, waits for the sensor to start and confirms from the status byte
+    /// that it is powered and passed its integrity test.
+    #[bisync]
+    pub async fn power_up<D: DelayNs>(&mut self, mut delay: D) -> Result<Status, I::Error> {
+        self.power.set_high().map_err(|_| Fault::Pin)?;
+        delay.delay_ms(POWER_UP_DELAY_MS).await;
+        let status = self.mpr.status().await?;
+        if !status.is_powered() {
+            return Err(Fault::NotPowered.into())
+        }
+        if !status.integrity_test_passed() {
+            return Err(Fault::IntegrityTest.into())
+        }
+        self.powered = true;
+        Ok(status)
+    }
+
+    /// Switches the supply off.
+    ///
+    /// Make sure the bus lines don't back-power the sensor through its I/O pins while it is off.
+    pub fn power_down(&mut self) -> Result<(), I::Error> {
+        self.power.set_low().map_err(|_| Fault::Pin)?;
+        self.powered = false;
+        Ok(())
+    }
+
+    /// Switches the supply off, waits for it to discharge and powers the sensor up again.
+    #[bisync]
+    pub async fn power_cycle<D: DelayNs>(&mut self, mut delay: D) -> Result<Status, I::Error> {
+        self.power_down()?;
+        delay.delay_ms(POWER_OFF_DELAY_MS).await;
+        self.power_up(delay).await
+    }
+
+    /// Powers the sensor up if needed, then exits standby, waits and reads a Reading.
+    ///
+    /// `IntegrityTest` and `NotPowered` errors, whether reported at power-up or by the read, are
+    /// retried after a power cycle up to the configured number of retries.
+    #[bisync]
+    pub async fn read_with_delay<D: DelayNs>(&mut self, mut delay: D) -> Result<Reading, I::Error> {
+        let mut retries = self.retries;
+        loop {
+            let result = match self.power_up_if_off(&mut delay).await {
+                Ok(()) => self.mpr.read_with_delay(&mut delay).await,
+                Err(e) => Err(e)
+            };
+            if !self.recover(&result, &mut retries, &mut delay).await? {
+                return self.finish(result)
+            }
+        }
+    }
+
+    /// Like `read_with_delay`, but waits for the EOC pin instead of a fixed delay.
+    #[bisync]
+    pub async fn read_with_eoc<E: Eoc, D: DelayNs>(
+        &mut self,
+        eoc: &mut E,
+        mut delay: D,
+        timeout_ms: Option<u32>
+    ) -> Result<Reading, I::Error> {
+        let mut retries = self.retries;
+        loop {
+            let result = match self.power_up_if_off(&mut delay).await {
+                Ok(()) => self.mpr.read_with_eoc(eoc, &mut delay, timeout_ms).await,
+                Err(e) => Err(e)
+            };
+            if !self.recover(&result, &mut retries, &mut delay).await? {
+                return self.finish(result)
+            }
+        }
+    }
+
+    /// Like `read_with_delay`, but polls the busy flag instead of waiting a fixed delay.
+    #[bisync]
+    pub async fn read_with_polling<D: DelayNs>(
+        &mut self,
+        mut delay: D,
+        poll_interval_us: u32,
+        timeout_ms: u32
+    ) -> Result<Reading, I::Error> {
+        let mut retries = self.retries;
+        loop {
+            let result = match self.power_up_if_off(&mut delay).await {
+                Ok(()) => self.mpr.read_with_polling(&mut delay, poll_interval_us, timeout_ms).await,
+                Err(e) => Err(e)
+            };
+            if !self.recover(&result, &mut retries, &mut delay).await? {
+                return self.finish(result)
+            }
+        }
+    }
+
+    #[bisync]
+    async fn power_up_if_off<D: DelayNs>(&mut self, delay: D) -> Result<(), I::Error> {
+        if !self.powered {
+            self.power_up(delay).await?;
+        }
+        Ok(())
+    }
+
+    /// Switches the supply off ahead of a retry if `result` is a power fault and retries are left.
+    #[bisync]
+    async fn recover<D: DelayNs>(
+        &mut self,
+        result: &Result<Reading, I::Error>,
+        retries: &mut u8,
+        mut delay: D
+    ) -> Result<bool, I::Error> {
+        match result {
+            Err(e) if *retries > 0 && I::is_power_fault(e) => {
+                *retries -= 1;
+                self.power_down()?;
+                delay.delay_ms(POWER_OFF_DELAY_MS).await;
+                Ok(true)
+            },
+            _ => Ok(false)
+        }
+    }
+
+    fn finish(&mut self, result: Result<Reading, I::Error>) -> Result<Reading, I::Error> {
+        if self.auto_power_down {
+            let powered_down = self.power_down();
+            return result.and_then(|reading| powered_down.map(|_| reading))
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use embedded_hal_mock::eh1::MockError;
+    use embedded_hal_mock::eh1::digital::{Mock as PinMock, State, Transaction as PinTransaction};
+    use std::io::ErrorKind;
+    use crate::{MprI2cError, MprSpiError};
+    use crate::sim::{test_config, SimFaults, SimMpr};
+    use super::super::block_on;
+    use super::super::driver::EXIT_STANDBY_DELAY_MS;
+    use super::*;
+
+    #[test]
+    fn power_up_ok() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = PoweredMpr::new(Mpr::sim_i2c(&sim), sim.power_pin());
+        let status = block_on(sensor.power_up(sim.delay())).unwrap();
+        assert!(status.is_powered());
+        assert!(sensor.is_powered());
+        assert_eq!(sim.now_us(), POWER_UP_DELAY_MS as u64 * 1_000);
+    }
+
+    #[test]
+    fn power_up_pin_error() {
+        let sim = SimMpr::new(test_config());
+        let mut pin = PinMock::new(&[PinTransaction::set(State::High).with_error(MockError::Io(ErrorKind::Other))]);
+        let mut sensor = PoweredMpr::new(Mpr::sim_i2c(&sim), pin.clone());
+        assert_eq!(block_on(sensor.power_up(sim.delay())), Err(MprI2cError::Pin));
+        assert!(!sensor.is_powered());
+        pin.done();
+    }
+
+    #[test]
+    fn power_up_integrity_error() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { integrity_error: true, ..Default::default() });
+        let mut sensor = PoweredMpr::new(Mpr::sim_spi(&sim), sim.power_pin());
+        assert_eq!(block_on(sensor.power_up(sim.delay())), Err(MprSpiError::IntegrityTest));
+        assert!(!sensor.is_powered());
+        assert!(sim.is_supplied());
+    }
+
+    #[test]
+    fn read_with_delay_auto_power_down() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(10.0);
+        let mut sensor = PoweredMpr::new(Mpr::sim_i2c(&sim), sim.power_pin());
+        sensor.set_auto_power_down(true);
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert!(relative_eq!(reading.psi(), 10.0, epsilon = 1e-4));
+        assert!(!sensor.is_powered());
+        assert!(!sim.is_supplied());
+        assert_eq!(sim.now_us(), (POWER_UP_DELAY_MS + EXIT_STANDBY_DELAY_MS) as u64 * 1_000);
+    }
+
+    #[test]
+    fn read_with_delay_power_cycles_on_integrity_error() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(10.0);
+        let mut sensor = PoweredMpr::new(Mpr::sim_spi(&sim), sim.power_pin());
+        block_on(sensor.power_up(sim.delay())).unwrap();
+        sim.set_faults(SimFaults { integrity_error: true, ..Default::default() });
+        let reading = block_on(sensor.read_with_delay(sim.delay())).unwrap();
+        assert!(relative_eq!(reading.psi(), 10.0, epsilon = 1e-4));
+        assert!(sensor.is_powered());
+    }
+
+    #[test]
+    fn read_with_polling_power_cycles_on_not_powered() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(12.5);
+        let mut sensor = PoweredMpr::new(Mpr::sim_i2c(&sim), sim.power_pin());
+        block_on(sensor.power_up(sim.delay())).unwrap();
+        sim.set_faults(SimFaults { not_powered: true, ..Default::default() });
+        let reading = block_on(sensor.read_with_polling(sim.delay(), 500, 50)).unwrap();
+        assert!(relative_eq!(reading.psi(), 12.5, epsilon = 1e-4));
+        assert!(sensor.is_powered());
+    }
+
+    #[test]
+    fn read_with_eoc_power_cycles_on_integrity_error() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(2.5);
+        let mut sensor = PoweredMpr::new(Mpr::sim_spi(&sim), sim.power_pin());
+        sensor.set_auto_power_down(true);
+        sim.set_faults(SimFaults { integrity_error: true, ..Default::default() });
+        let reading = block_on(sensor.read_with_eoc(&mut sim.eoc_pin(), sim.delay(), Some(10))).unwrap();
+        assert!(relative_eq!(reading.psi(), 2.5, epsilon = 1e-4));
+        assert!(!sim.is_supplied());
+    }
+
+    #[test]
+    fn read_with_delay_retries_exhausted() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = PoweredMpr::new(Mpr::sim_i2c(&sim), sim.power_pin());
+        sensor.set_retries(0);
+        block_on(sensor.power_up(sim.delay())).unwrap();
+        sim.set_faults(SimFaults { not_powered: true, ..Default::default() });
+        assert_eq!(block_on(sensor.read_with_delay(sim.delay())).err(), Some(MprI2cError::NotPowered));
+    }
+
+    #[test]
+    fn read_with_delay_other_errors_not_retried() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = PoweredMpr::new(Mpr::sim_spi(&sim), sim.power_pin());
+        sim.set_faults(SimFaults { math_saturation: true, ..Default::default() });
+        assert_eq!(block_on(sensor.read_with_delay(sim.delay())).err(), Some(MprSpiError::MathSaturation));
+        assert_eq!(sim.now_us(), (POWER_UP_DELAY_MS + EXIT_STANDBY_DELAY_MS) as u64 * 1_000);
+    }
+}
@@ -0,0 +1,20 @@
+use crate::registers::Status;
+
+/// I2C addresses an MPR can be ordered with.
+pub const VALID_I2C_ADDRESSES: [u8; 8] = [0x08, 0x18, 0x28, 0x38, 0x48, 0x58, 0x68, 0x78];
+
+/// Outcome of probing one address during a `scan`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Probe<E> {
+    /// The address was not acknowledged.
+    NotFound,
+    /// A device acknowledged and returned this status byte.
+    Found(Status),
+    /// The bus failed for a reason other than a missing acknowledge.
+    BusError(E)
+}
+impl<E> Probe<E> {
+    pub fn is_found(&self) -> bool {
+        matches!(self, Probe::Found(_))
+    }
+}
@@ -2,6 +2,7 @@ use bitfields::bitfield;
 
 /// MPR sensor status byte.
 #[bitfield(u8)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct Status {
     // LSB first
     #[bits(1, access = ro)]
@@ -0,0 +1,66 @@
+use embedded_hal::i2c::{Error, ErrorKind};
+use crate::{Probe, Status, VALID_I2C_ADDRESSES};
+use super::{bisync, I2c};
+
+/// Reads a status byte from every address in `VALID_I2C_ADDRESSES`, in order.
+///
+/// Any device that acknowledges is reported as found, so other parts sharing the bus at these
+/// addresses show up too; a powered, idle MPR reports `0x40`.
+#[bisync]
+pub async fn scan<I2C: I2c>(bus: &mut I2C) -> [(u8, Probe<I2C::Error>); VALID_I2C_ADDRESSES.len()] {
+    let mut probes = VALID_I2C_ADDRESSES.map(|address| (address, Probe::NotFound));
+    for (address, probe) in probes.iter_mut() {
+        let mut buf = [0u8; 1];
+        *probe = match bus.read(*address, &mut buf).await {
+            Ok(()) => Probe::Found(Status::from_bits(buf[0])),
+            Err(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)) => Probe::NotFound,
+            Err(e) => Probe::BusError(e)
+        };
+    }
+    probes
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use std::vec;
+    use std::vec::Vec;
+    use embedded_hal::i2c::NoAcknowledgeSource;
+    use crate::sim::{test_config, SimFaults, SimMpr};
+    use super::super::block_on;
+    use super::*;
+
+    #[test]
+    fn scan_sim_ok() {
+        let sim = SimMpr::new(test_config());
+        sim.set_address(0x28);
+        let probes = block_on(scan(&mut sim.i2c()));
+        let found: Vec<_> = probes.iter().filter(|(_, probe)| probe.is_found()).map(|(address, _)| *address).collect();
+        assert_eq!(found, vec![0x28]);
+        assert!(matches!(probes[2].1, Probe::Found(status) if status.is_powered() && !status.is_busy()));
+    }
+
+    #[test]
+    fn scan_reports_status() {
+        let sim = SimMpr::new(test_config());
+        sim.set_faults(SimFaults { integrity_error: true, ..Default::default() });
+        let probes = block_on(scan(&mut sim.i2c()));
+        assert!(matches!(probes[1], (0x18, Probe::Found(status)) if !status.integrity_test_passed()));
+    }
+
+    #[test]
+    fn scan_bus_error() {
+        let mut expectations: Vec<_> = VALID_I2C_ADDRESSES.iter()
+            .map(|address| I2cTransaction::read(*address, vec![0x40])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)))
+            .collect();
+        expectations[0] = I2cTransaction::read(0x08, vec![0x40]).with_error(ErrorKind::ArbitrationLoss);
+        expectations[7] = I2cTransaction::read(0x78, vec![0x40]);
+        let mut bus = I2cMock::new(&expectations);
+        let probes = block_on(scan(&mut bus));
+        assert_eq!(probes[0], (0x08, Probe::BusError(ErrorKind::ArbitrationLoss)));
+        assert_eq!(probes[1], (0x18, Probe::NotFound));
+        assert!(matches!(probes[7], (0x78, Probe::Found(_))));
+        bus.done();
+    }
+}
@@ -0,0 +1,467 @@
+//! Host-side model of an MPR sensor for exercising the driver without hardware.
+//!
+//! `SimMpr` holds the sensor state and a virtual clock, optionally a `SimClock` shared with other
+//! sensors. The handles returned by `i2c()`, `spi()`
+//! and `delay()` borrow it and implement the `embedded_hal` and `embedded_hal_async` traits, so
+//! the pressure, conversion time and address can be changed while a driver owns a handle.
+//! Time only passes when a `SimDelay` is used (or `advance_us` is called). Faults can be
+//! injected at any point with `set_faults`, and the supply switched with the pin returned by
+//! `power_pin()`. `eoc_pin()` models the end-of-conversion output.
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use embedded_hal::i2c::{ErrorKind as I2cErrorKind, NoAcknowledgeSource, Operation as I2cOperation};
+use embedded_hal::spi::{ErrorKind as SpiErrorKind, Operation as SpiOperation};
+use crate::{Clock, MprConfig};
+
+const DEFAULT_ADDRESS: u8 = 0x18;
+const DEFAULT_CONVERSION_TIME_US: u32 = 5_000;
+const OUTPUT_MEASUREMENT_CMD: u8 = 0xaa;
+const MAX_COUNTS: u32 = 0x00ff_ffff;
+const STATUS_MATH_SATURATION: u8 = 0b0000_0001;
+const STATUS_MEMORY_INTEGRITY: u8 = 0b0000_0100;
+const STATUS_BUSY: u8 = 0b0010_0000;
+const STATUS_POWERED: u8 = 0b0100_0000;
+// value read from an undriven bus
+const IDLE_BYTE: u8 = 0xff;
+
+/// Faults applied to every subsequent transaction until cleared.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SimFaults {
+    /// I2C transactions are not acknowledged and SPI transactions fail.
+    pub nak: bool,
+    /// Sets the math saturation bit in the status byte.
+    pub math_saturation: bool,
+    /// Sets the memory integrity error bit in the status byte. Cleared by a power cycle.
+    pub integrity_error: bool,
+    /// Clears the power indication bit; SPI reads return all zeros (power-on reset). Cleared by a
+    /// power cycle.
+    pub not_powered: bool,
+    /// Conversions never complete, so the busy bit stays set.
+    pub stuck_busy: bool,
+    /// Only the first `n` bytes of each read are driven; the rest read as `0xFF`.
+    pub truncated_read: Option<usize>,
+    /// Conversions complete without updating the output register.
+    pub frozen_output: bool,
+}
+
+/// Virtual time base shared by several `SimMpr`s, e.g. sensors on one bus.
+#[derive(Default)]
+pub struct SimClock {
+    now_ns: Cell<u64>
+}
+impl SimClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Virtual time since the clock was created.
+    pub fn now_us(&self) -> u64 {
+        self.now_ns.get() / 1_000
+    }
+}
+
+enum Time<'c> {
+    Own(Cell<u64>),
+    Shared(&'c SimClock)
+}
+
+/// Simulated MPR sensor.
+pub struct SimMpr<'c> {
+    config: MprConfig,
+    address: Cell<u8>,
+    conversion_time_ns: Cell<u64>,
+    conversion_end_ns: Cell<Option<u64>>,
+    faults: Cell<SimFaults>,
+    time: Time<'c>,
+    output: Cell<u32>,
+    raw_data: Cell<u32>,
+    supplied: Cell<bool>,
+}
+impl<'c> SimMpr<'c> {
+    /// Creates a powered, idle sensor at I2C address 0x18 with a 5ms conversion time. Pressures
+    /// are converted to counts with the range, unit and transfer function of `config`.
+    pub fn new(config: MprConfig) -> Self {
+        Self::with_time(config, Time::Own(Cell::new(0)))
+    }
+
+    /// Like `new`, but on the time base of `clock`, so a delay on any sensor sharing it advances
+    /// them all.
+    pub fn with_clock(config: MprConfig, clock: &'c SimClock) -> Self {
+        Self::with_time(config, Time::Shared(clock))
+    }
+
+    fn with_time(config: MprConfig, time: Time<'c>) -> Self {
+        Self {
+            config,
+            address: Cell::new(DEFAULT_ADDRESS),
+            conversion_time_ns: Cell::new(DEFAULT_CONVERSION_TIME_US as u64 * 1_000),
+            conversion_end_ns: Cell::new(None),
+            faults: Cell::new(SimFaults::default()),
+            time,
+            output: Cell::new(0),
+            raw_data: Cell::new(0),
+            supplied: Cell::new(true),
+        }
+    }
+
+    /// I2C bus handle.
+    pub fn i2c(&self) -> SimI2c<'_> {
+        SimI2c { sim: self }
+    }
+
+    /// SPI device handle.
+    pub fn spi(&self) -> SimSpi<'_> {
+        SimSpi { sim: self }
+    }
+
+    /// Supply pin handle: driving it low powers the sensor off (I2C transactions are not
+    /// acknowledged, SPI reads return all zeros) and driving it high powers it back on with the
+    /// output register and power-up faults cleared.
+    pub fn power_pin(&self) -> SimPowerPin<'_> {
+        SimPowerPin { sim: self }
+    }
+
+    /// EOC pin handle, high unless the status byte reports busy. Awaiting it high advances the
+    /// virtual clock to the end of the conversion in progress; with `stuck_busy` it never
+    /// completes.
+    pub fn eoc_pin(&self) -> SimEoc<'_> {
+        SimEoc { sim: self }
+    }
+
+    /// Delay handle that advances the virtual clock instead of sleeping. It also implements
+    /// `Clock`, reading the virtual clock.
+    pub fn delay(&self) -> SimDelay<'_> {
+        SimDelay { sim: self }
+    }
+
+    pub fn set_address(&self, address: u8) {
+        self.address.set(address);
+    }
+
+    pub fn set_conversion_time_us(&self, conversion_time_us: u32) {
+        self.conversion_time_ns.set(conversion_time_us as u64 * 1_000);
+    }
+
+    /// Sets the pressure, in the native unit of the configured range, latched by the next
+    /// conversion.
+    pub fn set_pressure(&self, pressure: f32) {
+        let transfer_function = self.config.transfer_function;
+        let span = transfer_function.max_counts() - transfer_function.min_counts();
+        let pressure_min = self.config.pressure_min as f32 / 1_000.0;
+        let pressure_max = self.config.pressure_max as f32 / 1_000.0;
+        let counts = (pressure - pressure_min) / (pressure_max - pressure_min) * span + transfer_function.min_counts();
+        // float to int casts saturate, so negative counts become 0
+        self.set_raw_data((counts + 0.5) as u32);
+    }
+
+    /// Sets the 24-bit count latched by the next conversion.
+    pub fn set_raw_data(&self, raw_data: u32) {
+        self.raw_data.set(raw_data.min(MAX_COUNTS));
+    }
+
+    pub fn faults(&self) -> SimFaults {
+        self.faults.get()
+    }
+
+    /// Replaces the injected faults; `SimFaults::default()` clears them.
+    pub fn set_faults(&self, faults: SimFaults) {
+        self.faults.set(faults);
+    }
+
+    /// Virtual time since the sensor (or its shared clock) was created.
+    pub fn now_us(&self) -> u64 {
+        self.now_ns().get() / 1_000
+    }
+
+    pub fn advance_us(&self, us: u32) {
+        self.advance_ns(us as u64 * 1_000);
+    }
+
+    /// Whether the supply pin is high.
+    pub fn is_supplied(&self) -> bool {
+        self.supplied.get()
+    }
+
+    /// Whether a conversion is in progress.
+    pub fn is_busy(&self) -> bool {
+        self.update();
+        self.conversion_end_ns.get().is_some()
+    }
+
+    fn set_supplied(&self, supplied: bool) {
+        if supplied && !self.supplied.get() {
+            self.conversion_end_ns.set(None);
+            self.output.set(0);
+            let faults = self.faults.get();
+            self.faults.set(SimFaults { integrity_error: false, not_powered: false, ..faults });
+        }
+        self.supplied.set(supplied);
+    }
+
+    fn finish_conversion(&self) {
+        if let Some(end_ns) = self.conversion_end_ns.get() && !self.faults.get().stuck_busy {
+            self.now_ns().set(self.now_ns().get().max(end_ns));
+        }
+    }
+
+    fn is_powered(&self) -> bool {
+        self.supplied.get() && !self.faults.get().not_powered
+    }
+
+    fn now_ns(&self) -> &Cell<u64> {
+        match &self.time {
+            Time::Own(now_ns) => now_ns,
+            Time::Shared(clock) => &clock.now_ns
+        }
+    }
+
+    fn advance_ns(&self, ns: u64) {
+        self.now_ns().set(self.now_ns().get() + ns);
+    }
+
+    fn update(&self) {
+        let faults = self.faults.get();
+        if faults.stuck_busy {
+            return
+        }
+        if let Some(end_ns) = self.conversion_end_ns.get() && self.now_ns().get() >= end_ns {
+            if !faults.frozen_output {
+                self.output.set(self.raw_data.get());
+            }
+            self.conversion_end_ns.set(None);
+        }
+    }
+
+    fn command(&self, cmd: u8) {
+        // no new commands are processed while busy or unpowered
+        if cmd == OUTPUT_MEASUREMENT_CMD && !self.is_busy() && self.is_powered() {
+            self.conversion_end_ns.set(Some(self.now_ns().get() + self.conversion_time_ns.get()));
+        }
+    }
+
+    fn status(&self) -> u8 {
+        let faults = self.faults.get();
+        let mut status = 0;
+        if self.is_powered() {
+            status |= STATUS_POWERED;
+        }
+        if self.is_busy() || faults.stuck_busy {
+            status |= STATUS_BUSY;
+        }
+        if faults.integrity_error {
+            status |= STATUS_MEMORY_INTEGRITY;
+        }
+        if faults.math_saturation {
+            status |= STATUS_MATH_SATURATION;
+        }
+        status
+    }
+
+    /// Status byte followed by the 24-bit output register, MSB first.
+    fn frame(&self) -> [u8; 4] {
+        let status = self.status();
+        let output = self.output.get().to_be_bytes();
+        [status, output[1], output[2], output[3]]
+    }
+
+    fn fill(&self, buf: &mut [u8], zeros_when_unpowered: bool) {
+        let faults = self.faults.get();
+        let frame = self.frame();
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = if !self.is_powered() && zeros_when_unpowered {
+                0x00
+            } else if faults.truncated_read.is_some_and(|len| i >= len) {
+                IDLE_BYTE
+            } else {
+                frame.get(i).copied().unwrap_or(0x00)
+            };
+        }
+    }
+
+    fn i2c_transaction(&self, address: u8, operations: &mut [I2cOperation<'_>]) -> Result<(), I2cErrorKind> {
+        if address != self.address.get() || self.faults.get().nak || !self.supplied.get() {
+            return Err(I2cErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+        }
+        for operation in operations {
+            match operation {
+                I2cOperation::Read(buf) => self.fill(buf, false),
+                I2cOperation::Write(buf) => if let Some(cmd) = buf.first() {
+                    self.command(*cmd);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn spi_transaction(&self, operations: &mut [SpiOperation<'_, u8>]) -> Result<(), SpiErrorKind> {
+        if self.faults.get().nak {
+            return Err(SpiErrorKind::Other)
+        }
+        for operation in operations {
+            match operation {
+                SpiOperation::Read(buf) => self.fill(buf, true),
+                SpiOperation::Write(buf) => if let Some(cmd) = buf.first() {
+                    self.command(*cmd);
+                },
+                SpiOperation::Transfer(read, write) => {
+                    // MISO is clocked out before the command on MOSI takes effect
+                    self.fill(read, true);
+                    if let Some(cmd) = write.first() {
+                        self.command(*cmd);
+                    }
+                },
+                SpiOperation::TransferInPlace(buf) => {
+                    let cmd = buf.first().copied();
+                    self.fill(buf, true);
+                    if let Some(cmd) = cmd {
+                        self.command(cmd);
+                    }
+                },
+                SpiOperation::DelayNs(ns) => self.advance_ns(*ns as u64),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 0 to 25 psi with transfer function A, the range most tests use.
+#[cfg(test)]
+pub(crate) fn test_config() -> MprConfig {
+    MprConfig::new(0.0, 25.0, crate::PressureUnit::Psi, crate::TransferFunction::A)
+}
+
+// I2C ---------------------------------------------------------------------------------------------
+
+pub struct SimI2c<'a> {
+    sim: &'a SimMpr<'a>
+}
+impl embedded_hal::i2c::ErrorType for SimI2c<'_> {
+    type Error = I2cErrorKind;
+}
+impl embedded_hal::i2c::I2c for SimI2c<'_> {
+    fn transaction(&mut self, address: u8, operations: &mut [I2cOperation<'_>]) -> Result<(), Self::Error> {
+        self.sim.i2c_transaction(address, operations)
+    }
+}
+impl embedded_hal_async::i2c::I2c for SimI2c<'_> {
+    async fn transaction(&mut self, address: u8, operations: &mut [I2cOperation<'_>]) -> Result<(), Self::Error> {
+        self.sim.i2c_transaction(address, operations)
+    }
+}
+
+// SPI ---------------------------------------------------------------------------------------------
+
+pub struct SimSpi<'a> {
+    sim: &'a SimMpr<'a>
+}
+impl embedded_hal::spi::ErrorType for SimSpi<'_> {
+    type Error = SpiErrorKind;
+}
+impl embedded_hal::spi::SpiDevice for SimSpi<'_> {
+    fn transaction(&mut self, operations: &mut [SpiOperation<'_, u8>]) -> Result<(), Self::Error> {
+        self.sim.spi_transaction(operations)
+    }
+}
+impl embedded_hal_async::spi::SpiDevice for SimSpi<'_> {
+    async fn transaction(&mut self, operations: &mut [SpiOperation<'_, u8>]) -> Result<(), Self::Error> {
+        self.sim.spi_transaction(operations)
+    }
+}
+
+// Power -------------------------------------------------------------------------------------------
+
+pub struct SimPowerPin<'a> {
+    sim: &'a SimMpr<'a>
+}
+impl embedded_hal::digital::ErrorType for SimPowerPin<'_> {
+    type Error = Infallible;
+}
+impl embedded_hal::digital::OutputPin for SimPowerPin<'_> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.sim.set_supplied(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.sim.set_supplied(true);
+        Ok(())
+    }
+}
+
+// EOC ---------------------------------------------------------------------------------------------
+
+pub struct SimEoc<'a> {
+    sim: &'a SimMpr<'a>
+}
+impl SimEoc<'_> {
+    fn is_busy(&self) -> bool {
+        self.sim.status() & STATUS_BUSY != 0
+    }
+}
+impl embedded_hal::digital::ErrorType for SimEoc<'_> {
+    type Error = Infallible;
+}
+impl embedded_hal::digital::InputPin for SimEoc<'_> {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.is_busy())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(self.is_busy())
+    }
+}
+// nothing else drives the pin while a future is pending, so waits that can't complete never do
+impl embedded_hal_async::digital::Wait for SimEoc<'_> {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        self.sim.finish_conversion();
+        if self.is_busy() {
+            core::future::pending::<()>().await;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        if !self.is_busy() {
+            core::future::pending::<()>().await;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        if !self.is_busy() {
+            core::future::pending::<()>().await;
+        }
+        self.wait_for_high().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_for_rising_edge().await
+    }
+}
+
+// Delay -------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy)]
+pub struct SimDelay<'a> {
+    sim: &'a SimMpr<'a>
+}
+impl embedded_hal::delay::DelayNs for SimDelay<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.sim.advance_ns(ns as u64);
+    }
+}
+impl embedded_hal_async::delay::DelayNs for SimDelay<'_> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.sim.advance_ns(ns as u64);
+    }
+}
+impl Clock for SimDelay<'_> {
+    fn now_us(&mut self) -> u64 {
+        self.sim.now_us()
+    }
+}
@@ -0,0 +1,108 @@
+use crate::error::private::Fault;
+use crate::{Clock, Reading};
+use super::{bisync, DelayNs};
+use super::driver::Mpr;
+use super::interface::Interface;
+
+/// Continuous sampling at a fixed period, created by `Mpr::stream`.
+///
+/// The next conversion is started right after each read so it runs while the consumer processes
+/// the reading. Call `next_reading` at least once per period; if a whole period is missed, an
+/// overrun error is returned once and the schedule restarts from that point. The blocking
+/// `Stream` is also an endless `Iterator`.
+pub struct Stream<'a, I, C, D> {
+    mpr: &'a mut Mpr<I>,
+    clock: C,
+    delay: D,
+    period_us: u64,
+    deadline_us: Option<u64>,
+}
+impl<'a, I: Interface, C: Clock, D: DelayNs> Stream<'a, I, C, D> {
+    pub(crate) fn new(mpr: &'a mut Mpr<I>, period_us: u32, clock: C, delay: D) -> Self {
+        Self { mpr, clock, delay, period_us: period_us as u64, deadline_us: None }
+    }
+
+    /// Waits for the next sample period and returns its reading.
+    #[bisync]
+    pub async fn next_reading(&mut self) -> Result<Reading, I::Error> {
+        let now_us = self.clock.now_us();
+        let deadline_us = match self.deadline_us {
+            Some(deadline_us) if now_us >= deadline_us + self.period_us => {
+                self.restart(now_us).await?;
+                return Err(Fault::Overrun.into())
+            },
+            Some(deadline_us) => deadline_us,
+            None => self.restart(now_us).await?
+        };
+        if now_us < deadline_us {
+            self.delay.delay_us((deadline_us - now_us) as u32).await;
+        }
+        let reading = self.mpr.read().await;
+        self.mpr.exit_standby().await?;
+        self.deadline_us = Some(deadline_us + self.period_us);
+        reading
+    }
+
+    #[bisync]
+    async fn restart(&mut self, now_us: u64) -> Result<u64, I::Error> {
+        self.mpr.exit_standby().await?;
+        let deadline_us = now_us + self.period_us;
+        self.deadline_us = Some(deadline_us);
+        Ok(deadline_us)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use crate::MprI2cError;
+    use crate::sim::{test_config, SimMpr};
+    use super::super::block_on;
+    use super::*;
+
+    #[test]
+    fn next_reading_paced() {
+        let sim = SimMpr::new(test_config());
+        sim.set_pressure(7.5);
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let mut stream = sensor.stream(20_000, sim.delay(), sim.delay());
+        for i in 1..=3 {
+            let reading = block_on(stream.next_reading()).unwrap();
+            assert!(relative_eq!(reading.psi(), 7.5, epsilon = 1e-4));
+            assert_eq!(sim.now_us(), i * 20_000);
+        }
+    }
+
+    #[test]
+    fn next_reading_late_within_period() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let mut stream = sensor.stream(20_000, sim.delay(), sim.delay());
+        block_on(stream.next_reading()).unwrap();
+        sim.advance_us(25_000);
+        block_on(stream.next_reading()).unwrap();
+        assert_eq!(sim.now_us(), 45_000);
+        block_on(stream.next_reading()).unwrap();
+        assert_eq!(sim.now_us(), 60_000);
+    }
+
+    #[test]
+    fn next_reading_overrun() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let mut stream = sensor.stream(20_000, sim.delay(), sim.delay());
+        block_on(stream.next_reading()).unwrap();
+        sim.advance_us(50_000);
+        assert_eq!(block_on(stream.next_reading()).err(), Some(MprI2cError::Overrun));
+        block_on(stream.next_reading()).unwrap();
+        assert_eq!(sim.now_us(), 90_000);
+    }
+
+    #[test]
+    fn next_reading_period_too_short() {
+        let sim = SimMpr::new(test_config());
+        let mut sensor = Mpr::sim_i2c(&sim);
+        let mut stream = sensor.stream(1_000, sim.delay(), sim.delay());
+        assert_eq!(block_on(stream.next_reading()).err(), Some(MprI2cError::Busy));
+    }
+}
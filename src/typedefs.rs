@@ -1,23 +1,181 @@
+use crate::{Calibration, ZeroOffset};
+
 const KPA_PER_PSI: f32 = 6.894757;
 const INHG_PER_PSI: f32 = 2.03602;
+const MBAR_PER_BAR: f32 = 1000.0;
 const MMHG_PER_PSI: f32 = 51.71492;
 const PSI_PER_BAR: f32 = 14.50377;
+pub(crate) const MAX_COUNTS: u32 = 0x00ff_ffff;
 
 /// Configures the driver for a specific pressure range and transfer function.
 /// See datasheet Figure 4 Pressure Range, Unit and Reference, and Figure 4 Output Type.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct MprConfig {
-    pub(crate) pressure_min: u8,
-    pub(crate) pressure_max: u8,
-    pub(crate) transfer_function: TransferFunction
+    // thousandths of `unit`, so integer conversions never touch floats
+    pub(crate) pressure_min: i32,
+    pub(crate) pressure_max: i32,
+    pub(crate) unit: PressureUnit,
+    pub(crate) transfer_function: TransferFunction,
+    pub(crate) calibration: Option<Calibration>
 }
 impl MprConfig {
+    /// `pressure_min` and `pressure_max` are expressed in `unit`, the native unit of the part
+    /// (e.g. `-1.0`, `1.0`, `PressureUnit::Psi` or `0.0`, `60.0`, `PressureUnit::Mbar`), and are
+    /// rounded to the nearest thousandth.
     pub fn new(
-        pressure_min: u8,
-        pressure_max: u8,
+        pressure_min: f32,
+        pressure_max: f32,
+        unit: PressureUnit,
         transfer_function: TransferFunction
     ) -> Self {
-        Self { pressure_min, pressure_max, transfer_function }
+        Self::new_milli(to_milli(pressure_min), to_milli(pressure_max), unit, transfer_function)
+    }
+
+    /// Float-free constructor; `pressure_min` and `pressure_max` are thousandths of `unit`
+    /// (e.g. `0`, `1_600`, `PressureUnit::Bar` for a 0 to 1.6 bar part).
+    pub fn new_milli(
+        pressure_min: i32,
+        pressure_max: i32,
+        unit: PressureUnit,
+        transfer_function: TransferFunction
+    ) -> Self {
+        Self { pressure_min, pressure_max, unit, transfer_function, calibration: None }
+    }
+
+    /// Attaches a calibration fitted for this range, applied to raw data by every conversion.
+    pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = Some(calibration);
+        self
+    }
+
+    pub fn calibration(&self) -> Option<&Calibration> {
+        self.calibration.as_ref()
+    }
+
+    /// Converts raw measurement data to millionths of the native unit using integer math only.
+    ///
+    /// Transfer function limits are exact, so the result is within 1 micro-unit (rounding) of
+    /// the ideal value. Valid for ranges spanning up to 100,000 units.
+    pub fn pressure_micro(&self, raw_data: u32) -> i64 {
+        let min_counts = self.transfer_function.min_decicounts();
+        let span_counts = self.transfer_function.max_decicounts() - min_counts;
+        let span_milli = (self.pressure_max - self.pressure_min) as i64;
+        // ~1.5e19 at full scale of a 100,000 unit span, so the product needs 128 bits
+        let offset = (self.decicounts(raw_data) - min_counts) as i128 * span_milli as i128 * 1_000;
+        self.pressure_min as i64 * 1_000 + div_round_wide(offset, span_counts as i128) as i64
+    }
+
+    /// Converts raw measurement data to pascals using integer math only.
+    ///
+    /// Matches `Reading::kpa() * 1000.0` within 1 Pa for all MPR catalog ranges; unit factors are
+    /// exact for bar, mbar and kPa and accurate to 7 significant digits for psi, inHg and mmHg.
+    /// Pressures beyond the `i32` range, about ±21 kbar, saturate.
+    pub fn pascals(&self, raw_data: u32) -> i32 {
+        let (numerator, denominator) = self.unit.pascals_ratio();
+        let pascals = div_round(self.pressure_micro(raw_data) * numerator, denominator * 1_000_000);
+        pascals.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+
+    /// Builds a Reading that converts `raw_data` with `zero_offset` subtracted and the calibration
+    /// applied.
+    pub(crate) fn reading(&self, raw_data: u32, zero_offset: ZeroOffset) -> Reading {
+        let counts = div_round(self.decicounts(zero_offset.apply(raw_data)), 10);
+        Reading::new(
+            self.pressure_min as f32 / 1_000.0,
+            self.pressure_max as f32 / 1_000.0,
+            self.unit,
+            raw_data,
+            self.transfer_function
+        ).with_correction((raw_data as i64 - counts) as i32)
+    }
+
+    /// Tenths of a count for `raw_data` after calibration.
+    fn decicounts(&self, raw_data: u32) -> i64 {
+        match &self.calibration {
+            Some(calibration) => calibration.ideal_decicounts(raw_data),
+            None => raw_data as i64 * 10
+        }
+    }
+
+    /// Tenths of a count that an ideal sensor outputs at `pressure_milli`.
+    pub(crate) fn ideal_decicounts(&self, pressure_milli: i32) -> i64 {
+        let min_counts = self.transfer_function.min_decicounts();
+        let span_counts = self.transfer_function.max_decicounts() - min_counts;
+        let span_milli = (self.pressure_max - self.pressure_min) as i64;
+        if span_milli == 0 {
+            return min_counts
+        }
+        min_counts + div_round((pressure_milli - self.pressure_min) as i64 * span_counts, span_milli)
+    }
+}
+
+pub(crate) fn to_milli(value: f32) -> i32 {
+    // float to int casts truncate toward zero
+    if value < 0.0 { (value * 1_000.0 - 0.5) as i32 } else { (value * 1_000.0 + 0.5) as i32 }
+}
+
+/// Integer division rounding half away from zero; `denominator` must be positive.
+pub(crate) fn div_round(numerator: i64, denominator: i64) -> i64 {
+    if numerator < 0 {
+        (numerator - denominator / 2) / denominator
+    } else {
+        (numerator + denominator / 2) / denominator
+    }
+}
+
+fn div_round_wide(numerator: i128, denominator: i128) -> i128 {
+    if numerator < 0 {
+        (numerator - denominator / 2) / denominator
+    } else {
+        (numerator + denominator / 2) / denominator
+    }
+}
+
+/// Pressure units used by MPR part numbers and `Reading` conversions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PressureUnit {
+    Bar,
+    Inhg,
+    Kpa,
+    Mbar,
+    Mmhg,
+    Psi
+}
+impl PressureUnit {
+    /// Converts a value expressed in this unit to PSI.
+    pub fn to_psi(&self, value: f32) -> f32 {
+        match self {
+            PressureUnit::Bar => value * PSI_PER_BAR,
+            PressureUnit::Inhg => value / INHG_PER_PSI,
+            PressureUnit::Kpa => value / KPA_PER_PSI,
+            PressureUnit::Mbar => value * PSI_PER_BAR / MBAR_PER_BAR,
+            PressureUnit::Mmhg => value / MMHG_PER_PSI,
+            PressureUnit::Psi => value,
+        }
+    }
+
+    /// Pascals per unit as a numerator/denominator pair for integer conversions.
+    fn pascals_ratio(&self) -> (i64, i64) {
+        match self {
+            PressureUnit::Bar => (100_000, 1),
+            PressureUnit::Inhg => (3_386_389, 1_000),
+            PressureUnit::Kpa => (1_000, 1),
+            PressureUnit::Mbar => (100, 1),
+            PressureUnit::Mmhg => (1_333_224, 10_000),
+            PressureUnit::Psi => (6_894_757, 1_000),
+        }
+    }
+
+    /// Converts a value expressed in PSI to this unit.
+    pub fn from_psi(&self, psi: f32) -> f32 {
+        match self {
+            PressureUnit::Bar => psi / PSI_PER_BAR,
+            PressureUnit::Inhg => psi * INHG_PER_PSI,
+            PressureUnit::Kpa => psi * KPA_PER_PSI,
+            PressureUnit::Mbar => psi / PSI_PER_BAR * MBAR_PER_BAR,
+            PressureUnit::Mmhg => psi * MMHG_PER_PSI,
+            PressureUnit::Psi => psi,
+        }
     }
 }
 
@@ -25,35 +183,105 @@ impl MprConfig {
 pub struct Reading {
     pub pressure_min: f32,
     pub pressure_max: f32,
+    pub unit: PressureUnit,
     pub raw_data: u32,
-    pub transfer_function: TransferFunction
+    pub transfer_function: TransferFunction,
+    /// Counts subtracted from `raw_data` before conversion, combining the `ZeroOffset` and
+    /// `Calibration` applied by the driver.
+    pub correction: i32
 }
 impl Reading {
-    pub fn new(range_min: f32, range_max: f32, raw_data: u32, transfer_function: TransferFunction) -> Self {
-        Self { pressure_min: range_min, pressure_max: range_max, raw_data, transfer_function }
+    pub fn new(
+        pressure_min: f32,
+        pressure_max: f32,
+        unit: PressureUnit,
+        raw_data: u32,
+        transfer_function: TransferFunction
+    ) -> Self {
+        Self { pressure_min, pressure_max, unit, raw_data, transfer_function, correction: 0 }
     }
 
-    /// Converts raw measurement data to bar.
-    pub fn bar(&self) -> f32 {
-        self.psi() / PSI_PER_BAR
+    /// Subtracts `correction` counts from `raw_data` in the conversions.
+    pub fn with_correction(mut self, correction: i32) -> Self {
+        self.correction = correction;
+        self
     }
 
+    /// Converts raw measurement data to bar.
+    pub fn bar(&self) -> f32 { self.to(PressureUnit::Bar) }
+
     /// Converts raw measurement data to inHg.
-    pub fn inhg(&self) -> f32 { self.psi() * INHG_PER_PSI }
+    pub fn inhg(&self) -> f32 { self.to(PressureUnit::Inhg) }
+
+    /// Converts raw measurement data to mbar.
+    pub fn mbar(&self) -> f32 { self.to(PressureUnit::Mbar) }
 
     /// Converts raw measurement data to mmHg.
-    pub fn mmhg(&self) -> f32 { self.psi() * MMHG_PER_PSI }
+    pub fn mmhg(&self) -> f32 { self.to(PressureUnit::Mmhg) }
 
     /// Converts raw measurement data to kPa.
-    pub fn kpa(&self) -> f32 {
-        self.psi() * KPA_PER_PSI
-    }
+    pub fn kpa(&self) -> f32 { self.to(PressureUnit::Kpa) }
 
     /// Converts raw measurement data to PSI.
-    pub fn psi(&self) -> f32 {
-        ((self.raw_data as f32 - self.transfer_function.min_counts()) * (self.pressure_max - self.pressure_min)) /
+    pub fn psi(&self) -> f32 { self.to(PressureUnit::Psi) }
+
+    /// Converts raw measurement data to the native unit of the configured pressure range.
+    pub fn pressure(&self) -> f32 {
+        ((self.raw_data as f32 - self.correction as f32 - self.transfer_function.min_counts()) * (self.pressure_max - self.pressure_min)) /
             (self.transfer_function.max_counts() - self.transfer_function.min_counts()) + self.pressure_min
     }
+
+    /// Converts raw measurement data to `unit`.
+    pub fn to(&self, unit: PressureUnit) -> f32 {
+        if unit == self.unit {
+            return self.pressure()
+        }
+        unit.from_psi(self.unit.to_psi(self.pressure()))
+    }
+
+    /// Classifies the corrected counts against the transfer function limits. Conversions of
+    /// out-of-range readings are extrapolated.
+    pub fn range_status(&self) -> RangeStatus {
+        let counts = self.raw_data as i64 - self.correction as i64;
+        if counts * 10 < self.transfer_function.min_decicounts() {
+            RangeStatus::UnderRange
+        } else if counts * 10 > self.transfer_function.max_decicounts() {
+            RangeStatus::OverRange
+        } else {
+            RangeStatus::InRange
+        }
+    }
+
+    /// Limits the corrected counts to the transfer function limits, so conversions stay within the
+    /// pressure range. `raw_data` is unchanged.
+    pub fn clamped(mut self) -> Self {
+        let counts = self.raw_data as i64 - self.correction as i64;
+        // whole counts just inside the limits
+        let min_counts = (self.transfer_function.min_decicounts() + 9) / 10;
+        let max_counts = self.transfer_function.max_decicounts() / 10;
+        self.correction = (self.raw_data as i64 - counts.clamp(min_counts, max_counts)) as i32;
+        self
+    }
+}
+
+/// Position of a Reading relative to the transfer function limits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RangeStatus {
+    UnderRange,
+    InRange,
+    OverRange
+}
+
+/// How the driver handles readings outside the transfer function limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RangePolicy {
+    /// Out-of-range readings are returned as is and convert to pressures outside the range.
+    #[default]
+    Extrapolate,
+    /// Out-of-range readings are clamped to the range limits.
+    Clamp,
+    /// Out-of-range readings are reported as an `OutOfRange` error.
+    Error
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -63,15 +291,30 @@ pub enum TransferFunction {
     /// 2.5% to 22.5% of 2**24 counts
     B,
     /// 20% to 80% of 2**24 counts
-    C
+    C,
+    /// Counts of OEM-calibrated and special-order parts, see `TransferFunction::custom`
+    Custom(CustomCounts)
 }
 impl TransferFunction {
+    /// Validated custom transfer function; `min_counts` must be below `max_counts` and both must
+    /// fit in the 24-bit output.
+    pub fn custom(min_counts: u32, max_counts: u32) -> Result<Self, TransferFunctionError> {
+        if max_counts > MAX_COUNTS {
+            return Err(TransferFunctionError::CountsOutOfRange)
+        }
+        if min_counts >= max_counts {
+            return Err(TransferFunctionError::InvalidRange)
+        }
+        Ok(TransferFunction::Custom(CustomCounts { min_counts, max_counts }))
+    }
+
     pub fn min_counts(&self) -> f32 {
         match self {
             // precomputed percentages of 2**24
             TransferFunction::A => 1677721.6,
             TransferFunction::B => 419430.4,
             TransferFunction::C => 3355443.3,
+            TransferFunction::Custom(counts) => counts.min_counts as f32,
         }
     }
     pub fn max_counts(&self) -> f32 {
@@ -80,10 +323,55 @@ impl TransferFunction {
             TransferFunction::A => 15099494.0,
             TransferFunction::B => 3774873.5,
             TransferFunction::C => 13421773.0,
+            TransferFunction::Custom(counts) => counts.max_counts as f32,
+        }
+    }
+
+    // exact tenths of a count, since every percentage of 2**24 has at most one decimal place
+    pub(crate) fn min_decicounts(&self) -> i64 {
+        match self {
+            TransferFunction::A => 16_777_216,
+            TransferFunction::B => 4_194_304,
+            TransferFunction::C => 33_554_432,
+            TransferFunction::Custom(counts) => counts.min_counts as i64 * 10,
+        }
+    }
+
+    pub(crate) fn max_decicounts(&self) -> i64 {
+        match self {
+            TransferFunction::A => 150_994_944,
+            TransferFunction::B => 37_748_736,
+            TransferFunction::C => 134_217_728,
+            TransferFunction::Custom(counts) => counts.max_counts as i64 * 10,
         }
     }
 }
 
+/// Output counts at the minimum and maximum of the pressure range, built with
+/// `TransferFunction::custom`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CustomCounts {
+    min_counts: u32,
+    max_counts: u32
+}
+impl CustomCounts {
+    pub fn min_counts(&self) -> u32 {
+        self.min_counts
+    }
+
+    pub fn max_counts(&self) -> u32 {
+        self.max_counts
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferFunctionError {
+    /// `min_counts` is not below `max_counts`.
+    InvalidRange,
+    /// A count does not fit in 24 bits.
+    CountsOutOfRange
+}
+
 #[cfg(test)]
 mod tests {
     use approx::relative_eq;
@@ -91,31 +379,184 @@ mod tests {
 
     #[test]
     fn reading_bar_ok() {
-        let reading = Reading::new(-1.0, 1.0, 14260634, TransferFunction::A);
+        let reading = Reading::new(-1.0, 1.0, PressureUnit::Psi, 14260634, TransferFunction::A);
         let _ = relative_eq!(reading.bar(), 0.0603, epsilon = f32::EPSILON);
     }
 
     #[test]
     fn reading_inhg_ok() {
-        let reading = Reading::new(-1.0, 1.0, 14260634, TransferFunction::A);
+        let reading = Reading::new(-1.0, 1.0, PressureUnit::Psi, 14260634, TransferFunction::A);
         let _ = relative_eq!(reading.inhg(), 1.78151, epsilon = f32::EPSILON);
     }
 
     #[test]
     fn reading_mmhg_ok() {
-        let reading = Reading::new(-1.0, 1.0, 14260634, TransferFunction::A);
+        let reading = Reading::new(-1.0, 1.0, PressureUnit::Psi, 14260634, TransferFunction::A);
         let _ = relative_eq!(reading.mmhg(), 45.25056, epsilon = f32::EPSILON);
     }
 
     #[test]
     fn reading_kpa_ok() {
-        let reading = Reading::new(-1.0, 1.0, 14260634, TransferFunction::A);
+        let reading = Reading::new(-1.0, 1.0, PressureUnit::Psi, 14260634, TransferFunction::A);
         let _ = relative_eq!(reading.kpa(), 6.0329, epsilon = f32::EPSILON);
     }
 
     #[test]
     fn reading_psi_ok() {
-        let reading = Reading::new(-1.0, 1.0, 14260634, TransferFunction::A);
+        let reading = Reading::new(-1.0, 1.0, PressureUnit::Psi, 14260634, TransferFunction::A);
         let _ = relative_eq!(reading.psi(), 0.875, epsilon = f32::EPSILON);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn reading_native_mbar_ok() {
+        let reading = Reading::new(0.0, 60.0, PressureUnit::Mbar, 8388608, TransferFunction::A);
+        assert!(relative_eq!(reading.pressure(), 30.0, epsilon = 1e-4));
+        assert!(relative_eq!(reading.mbar(), 30.0, epsilon = 1e-4));
+        assert!(relative_eq!(reading.bar(), 0.03, epsilon = 1e-6));
+        assert!(relative_eq!(reading.kpa(), 3.0, epsilon = 1e-3));
+        assert!(relative_eq!(reading.psi(), 0.435113, epsilon = 1e-5));
+    }
+
+    #[test]
+    fn reading_native_kpa_ok() {
+        let reading = Reading::new(0.0, 100.0, PressureUnit::Kpa, 8388608, TransferFunction::A);
+        assert!(relative_eq!(reading.kpa(), 50.0, epsilon = 1e-4));
+        assert!(relative_eq!(reading.psi(), 7.251886, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn reading_negative_fractional_range_ok() {
+        let reading = Reading::new(-2.5, 2.5, PressureUnit::Psi, 1677722, TransferFunction::A);
+        assert!(relative_eq!(reading.psi(), -2.5, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn reading_with_correction_ok() {
+        let reading = Reading::new(0.0, 25.0, PressureUnit::Psi, 1_677_822, TransferFunction::A)
+            .with_correction(100);
+        assert_eq!(reading.raw_data, 1_677_822);
+        assert!(relative_eq!(reading.psi(), 0.0, epsilon = 1e-4));
+    }
+
+    #[test]
+    fn transfer_function_custom_ok() {
+        let transfer_function = TransferFunction::custom(1_000_000, 16_000_000).unwrap();
+        assert_eq!(transfer_function.min_counts(), 1_000_000.0);
+        assert_eq!(transfer_function.max_counts(), 16_000_000.0);
+        let reading = Reading::new(0.0, 15.0, PressureUnit::Psi, 8_500_000, transfer_function);
+        assert!(relative_eq!(reading.psi(), 7.5, epsilon = 1e-4));
+        let config = MprConfig::new_milli(0, 15_000, PressureUnit::Psi, transfer_function);
+        assert_eq!(config.pressure_micro(8_500_000), 7_500_000);
+    }
+
+    #[test]
+    fn transfer_function_custom_invalid() {
+        assert_eq!(TransferFunction::custom(2_000, 2_000), Err(TransferFunctionError::InvalidRange));
+        assert_eq!(TransferFunction::custom(3_000, 2_000), Err(TransferFunctionError::InvalidRange));
+        assert_eq!(TransferFunction::custom(0, 0x0100_0000), Err(TransferFunctionError::CountsOutOfRange));
+        assert!(TransferFunction::custom(0, 0x00ff_ffff).is_ok());
+    }
+
+    #[test]
+    fn reading_range_status_ok() {
+        let reading = |raw_data| Reading::new(0.0, 25.0, PressureUnit::Psi, raw_data, TransferFunction::A);
+        assert_eq!(reading(1_677_721).range_status(), RangeStatus::UnderRange);
+        assert_eq!(reading(1_677_722).range_status(), RangeStatus::InRange);
+        assert_eq!(reading(15_099_494).range_status(), RangeStatus::InRange);
+        assert_eq!(reading(15_099_495).range_status(), RangeStatus::OverRange);
+        assert_eq!(reading(15_099_595).with_correction(101).range_status(), RangeStatus::InRange);
+    }
+
+    #[test]
+    fn reading_clamped_ok() {
+        let reading = Reading::new(0.0, 25.0, PressureUnit::Psi, 100, TransferFunction::A).clamped();
+        assert_eq!(reading.raw_data, 100);
+        assert_eq!(reading.range_status(), RangeStatus::InRange);
+        assert!(relative_eq!(reading.psi(), 0.0, epsilon = 1e-4));
+        let reading = Reading::new(0.0, 25.0, PressureUnit::Psi, 16_000_000, TransferFunction::A)
+            .with_correction(-50)
+            .clamped();
+        assert!(relative_eq!(reading.psi(), 25.0, epsilon = 1e-4));
+        let reading = Reading::new(0.0, 25.0, PressureUnit::Psi, 8_388_608, TransferFunction::A).clamped();
+        assert_eq!(reading.correction, 0);
+    }
+
+    #[test]
+    fn pressure_unit_round_trip_ok() {
+        for unit in [
+            PressureUnit::Bar,
+            PressureUnit::Inhg,
+            PressureUnit::Kpa,
+            PressureUnit::Mbar,
+            PressureUnit::Mmhg,
+            PressureUnit::Psi
+        ] {
+            assert!(relative_eq!(unit.to_psi(unit.from_psi(12.5)), 12.5, epsilon = 1e-4));
+        }
+    }
+
+    #[test]
+    fn config_new_rounds_to_milli() {
+        assert_eq!(
+            MprConfig::new(-2.5, 1.6, PressureUnit::Bar, TransferFunction::A),
+            MprConfig::new_milli(-2_500, 1_600, PressureUnit::Bar, TransferFunction::A)
+        );
+    }
+
+    #[test]
+    fn config_pressure_micro_ok() {
+        let config = MprConfig::new_milli(0, 25_000, PressureUnit::Psi, TransferFunction::A);
+        assert_eq!(config.pressure_micro(8388608), 12_500_000);
+        let config = MprConfig::new_milli(-1_000, 1_000, PressureUnit::Psi, TransferFunction::A);
+        assert_eq!(config.pressure_micro(14260634), 875_000);
+        let config = MprConfig::new_milli(0, 60_000, PressureUnit::Mbar, TransferFunction::C);
+        assert_eq!(config.pressure_micro(3355443), -1);
+    }
+
+    #[test]
+    fn config_pressure_micro_max_span() {
+        let config = MprConfig::new_milli(0, 100_000_000, PressureUnit::Mbar, TransferFunction::A);
+        assert_eq!(config.pressure_micro(0xff_ffff), 112_499_992_549);
+        assert_eq!(config.pressure_micro(0), -12_500_000_000);
+    }
+
+    #[test]
+    fn config_pascals_ok() {
+        let config = MprConfig::new_milli(0, 1_000, PressureUnit::Bar, TransferFunction::B);
+        assert_eq!(config.pascals(2097152), 50_000);
+        let config = MprConfig::new_milli(0, 25_000, PressureUnit::Psi, TransferFunction::A);
+        assert_eq!(config.pascals(8388608), 86_184);
+    }
+
+    #[test]
+    fn config_pascals_saturates() {
+        let config = MprConfig::new_milli(0, 100_000_000, PressureUnit::Bar, TransferFunction::A);
+        assert_eq!(config.pascals(0xff_ffff), i32::MAX);
+        let config = MprConfig::new_milli(-100_000_000, 0, PressureUnit::Bar, TransferFunction::A);
+        assert_eq!(config.pascals(0), i32::MIN);
+    }
+
+    #[test]
+    fn config_pascals_matches_float() {
+        let ranges = [
+            (0, 25_000, PressureUnit::Psi),
+            (-1_000, 1_000, PressureUnit::Psi),
+            (0, 60_000, PressureUnit::Mbar),
+            (0, 2_500, PressureUnit::Bar),
+            (0, 250_000, PressureUnit::Kpa),
+            (0, 300_000, PressureUnit::Mmhg),
+            (0, 30_000, PressureUnit::Inhg),
+        ];
+        for (pressure_min, pressure_max, unit) in ranges {
+            for transfer_function in [TransferFunction::A, TransferFunction::B, TransferFunction::C] {
+                let config = MprConfig::new_milli(pressure_min, pressure_max, unit, transfer_function);
+                for raw_data in (0..=0x00ff_ffff).step_by(65_521) {
+                    let expected = config.reading(raw_data, ZeroOffset::default()).kpa() * 1_000.0;
+                    let actual = config.pascals(raw_data) as f32;
+                    assert!((expected - actual).abs() <= 1.0, "{expected} != {actual}");
+                }
+            }
+        }
+    }
+}
+